@@ -37,6 +37,17 @@ mod trie {
             builder.build()
             // TODO print memory footprint compared to original `edict.furigana` file
         };
+
+        // Every 100th word of the dictionary, to look up many different keys.
+        static ref WORDS_EDICT: Vec<String> = {
+            let repo_root = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR environment variable must be set.");
+            let edict2_path = format!("{}/benches/edict.furigana", repo_root);
+            BufReader::new(File::open(edict2_path).unwrap())
+                .lines()
+                .step_by(100)
+                .map(|result| result.unwrap())
+                .collect()
+        };
     }
 
     pub fn build(c: &mut Criterion) {
@@ -100,6 +111,54 @@ mod trie {
         );
     }
 
+    pub fn exact_match_words(c: &mut Criterion) {
+        c.bench_function(
+            &format!(
+                "[{}] Trie::exact_match() {} words",
+                super::git_hash(),
+                WORDS_EDICT.len()
+            ),
+            move |b| {
+                b.iter_batched(
+                    || (&TRIE_EDICT, &WORDS_EDICT),
+                    |(trie, words)| {
+                        // Each label of each word is a child lookup.
+                        for word in words.iter() {
+                            assert!(trie.exact_match(word));
+                        }
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    pub fn inc_search_words(c: &mut Criterion) {
+        c.bench_function(
+            &format!(
+                "[{}] IncSearch::query() {} words",
+                super::git_hash(),
+                WORDS_EDICT.len()
+            ),
+            move |b| {
+                b.iter_batched(
+                    || (&TRIE_EDICT, &WORDS_EDICT),
+                    |(trie, words)| {
+                        // One query per label, like typing each word.
+                        let mut search = trie.inc_search();
+                        for word in words.iter() {
+                            search.reset();
+                            for label in word.bytes() {
+                                black_box(search.query(&label).unwrap());
+                            }
+                        }
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
     pub fn predictive_search(c: &mut Criterion) {
         let times = 100;
 
@@ -259,6 +318,8 @@ criterion_group!(
     benches,
     trie::build,
     trie::exact_match,
+    trie::exact_match_words,
+    trie::inc_search_words,
     trie::predictive_search,
     trie::predictive_search_big_output,
     trie::predictive_search_limited_big_output,
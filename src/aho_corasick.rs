@@ -0,0 +1,783 @@
+//! Aho-Corasick matching of a trie's keys inside a text
+//!
+//! An [AhoCorasick] matcher finds the occurrences of every key of a trie in a
+//! text. Matches are `(start, end, &Value)`, where `text[start..end]` is the
+//! key.
+//!
+//! Finding every match, or the matches of [MatchKind::Standard], takes a
+//! single pass, in time proportional to the length of the text plus the
+//! number of matches. The leftmost kinds may have to read on past a match, for
+//! up to the length of the longest key, before deciding on it, and the search
+//! for the next match starts over at its end. So in the worst case they take
+//! time proportional to the length of the text times the length of the
+//! longest key.
+//!
+//! ```rust
+//! use trie::map::Trie;
+//! use trie::aho_corasick::MatchKind;
+//!
+//! let trie = Trie::from_iter([("he", 0), ("she", 1), ("hers", 2)].map(|(k, v)| (k.bytes(), v)));
+//! let ac = trie.aho_corasick(MatchKind::Standard);
+//!
+//! let matches: Vec<_> = ac.find_overlapping_iter("ushers").collect();
+//! assert_eq!(matches, [(1, 4, &1), (2, 4, &0), (2, 6, &2)]);
+//!
+//! let matches: Vec<_> = ac.find_iter("ushers").collect();
+//! assert_eq!(matches, [(1, 4, &1)]);
+//! ```
+//!
+//! The failure links are computed once when the matcher is created, and are
+//! indexed by node number next to the trie they borrow.
+
+use crate::internal_data_structure::packed_ints::PackedInts;
+use crate::map::Trie;
+use louds::LoudsNodeNum;
+use std::collections::VecDeque;
+
+/// How [AhoCorasick::find_iter] picks among overlapping matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchKind {
+    /// Report the match that ends first, the longest one if several do, and
+    /// resume after it.
+    #[default]
+    Standard,
+    /// Report the match that starts first, the one whose key was inserted
+    /// first if several do, and resume after it.
+    ///
+    /// The trie only keeps the insertion order if it was built with
+    /// [BuildOptions::insertion_order][crate::map::BuildOptions::insertion_order].
+    /// Otherwise the keys count as inserted in lexicographical order, so the
+    /// shortest match wins. With the keys `Samwise` and `Sam`, inserted in that
+    /// order, this reports `Samwise` in `Samwise` if the order was kept, and
+    /// `Sam` if not.
+    LeftmostFirst,
+    /// Report the match that starts first, the longest one if several do, and
+    /// resume after it.
+    LeftmostLongest,
+}
+
+#[derive(Debug, Clone)]
+/// Finds the keys of a [Trie] inside a text.
+pub struct AhoCorasick<'a, Label, Value> {
+    trie: &'a Trie<Label, Value>,
+    kind: MatchKind,
+    /// The node of the longest proper suffix of each node's key that is a
+    /// prefix of some key, indexed by node number.
+    fail: PackedInts,
+    /// The nearest terminal along each node's failure links, or 0 if there is
+    /// none, indexed by node number.
+    output: PackedInts,
+    /// The length of each node's key, indexed by node number.
+    depth: PackedInts,
+}
+
+const ROOT: u64 = 1;
+
+impl<'a, Label: Ord, Value> AhoCorasick<'a, Label, Value> {
+    /// Create a matcher for the keys of `trie`.
+    ///
+    /// The failure links, outputs and key lengths are packed into as many bits
+    /// each as the largest of them takes, so _log n_ bits per node for _n_
+    /// nodes.
+    pub fn new(trie: &'a Trie<Label, Value>, kind: MatchKind) -> Self {
+        let num_nodes = trie.num_nodes();
+        let mut fail = vec![ROOT as usize; num_nodes + 1];
+        let mut output = vec![0; num_nodes + 1];
+        let mut depth = vec![0; num_nodes + 1];
+
+        // Nodes are numbered breadth first, so the failure links of every
+        // shallower node are known by the time a node is reached.
+        for parent in ROOT..=num_nodes as u64 {
+            for child in trie.children_node_nums(LoudsNodeNum(parent)) {
+                let c = child.0 as usize;
+                depth[c] = depth[parent as usize] + 1;
+                if parent != ROOT {
+                    let f = next_state(
+                        trie,
+                        |node| fail[node as usize] as u64,
+                        fail[parent as usize] as u64,
+                        trie.label(child),
+                    );
+                    fail[c] = f as usize;
+                    output[c] = if trie.is_terminal(LoudsNodeNum(f)) {
+                        f as usize
+                    } else {
+                        output[f as usize]
+                    };
+                }
+            }
+        }
+        Self {
+            trie,
+            kind,
+            fail: PackedInts::new(&fail),
+            output: PackedInts::new(&output),
+            depth: PackedInts::new(&depth),
+        }
+    }
+
+    /// Return the [MatchKind] of [find_iter()][AhoCorasick::find_iter].
+    pub fn match_kind(&self) -> MatchKind {
+        self.kind
+    }
+
+    /// Return every match in `text`, including overlapping ones, by end then
+    /// from longest to shortest. Ignores the [MatchKind].
+    pub fn find_overlapping_iter<'s>(
+        &'s self,
+        text: &'s (impl AsRef<[Label]> + ?Sized),
+    ) -> FindOverlappingIter<'s, 'a, Label, Value> {
+        FindOverlappingIter {
+            ac: self,
+            text: text.as_ref(),
+            end: 0,
+            state: ROOT,
+            out: 0,
+        }
+    }
+
+    /// Return the non-overlapping matches in `text` picked by the
+    /// [MatchKind], from left to right.
+    ///
+    /// Only [MatchKind::Standard] is guaranteed a single pass over `text`, see
+    /// the [module docs][self].
+    pub fn find_iter<'s>(
+        &'s self,
+        text: &'s (impl AsRef<[Label]> + ?Sized),
+    ) -> FindIter<'s, 'a, Label, Value> {
+        FindIter {
+            ac: self,
+            text: text.as_ref(),
+            pos: 0,
+        }
+    }
+
+    /// Return a copy of `text` where every match of
+    /// [find_iter()][AhoCorasick::find_iter] is replaced by its value.
+    ///
+    /// ```rust
+    /// use trie::map::Trie;
+    /// use trie::aho_corasick::MatchKind;
+    ///
+    /// let trie = Trie::from_iter([("cat", "dog"), ("category", "kind")].map(|(k, v)| (k.bytes(), v)));
+    /// let ac = trie.aho_corasick(MatchKind::LeftmostLongest);
+    /// assert_eq!(ac.replace_all("a cat category"), b"a dog kind");
+    /// ```
+    pub fn replace_all(&self, text: &(impl AsRef<[Label]> + ?Sized)) -> Vec<Label>
+    where
+        Label: Clone,
+        Value: AsRef<[Label]>,
+    {
+        let text = text.as_ref();
+        let mut replaced = Vec::with_capacity(text.len());
+        let mut last = 0;
+        for (start, end, value) in self.find_iter(text) {
+            replaced.extend_from_slice(&text[last..start]);
+            replaced.extend_from_slice(value.as_ref());
+            last = end;
+        }
+        replaced.extend_from_slice(&text[last..]);
+        replaced
+    }
+
+    /// Return the state after reading `label` in `state`.
+    fn next_state(&self, state: u64, label: &Label) -> u64 {
+        next_state(self.trie, |node| self.fail(node), state, label)
+    }
+
+    /// Return the longest key that ends in `state`, or 0 if there is none.
+    fn first_output(&self, state: u64) -> u64 {
+        if self.trie.is_terminal(LoudsNodeNum(state)) {
+            state
+        } else {
+            self.output(state)
+        }
+    }
+
+    /// Return where the key of the terminal `node` comes for
+    /// [MatchKind::LeftmostFirst]: its insertion rank if the trie kept them, or
+    /// else its node number, which orders the keys that start at the same
+    /// place from shortest to longest.
+    fn order(&self, node: u64) -> usize {
+        let node = LoudsNodeNum(node);
+        self.trie.insertion_rank(node).unwrap_or(node.0 as usize)
+    }
+
+    /// Return the earliest [order()][AhoCorasick::order] of a key under `node`.
+    fn first_order(&self, node: u64) -> usize {
+        let node = LoudsNodeNum(node);
+        self.trie
+            .first_insertion_rank(node)
+            .unwrap_or(node.0 as usize)
+    }
+
+    fn fail(&self, node: u64) -> u64 {
+        self.fail.get(node as usize) as u64
+    }
+
+    /// Return the next key along the failure links of `node`, or 0 if there is
+    /// none.
+    fn output(&self, node: u64) -> u64 {
+        self.output.get(node as usize) as u64
+    }
+
+    /// Return the length of the key of `node`.
+    fn depth(&self, node: u64) -> usize {
+        self.depth.get(node as usize)
+    }
+
+    /// Find the first match in `text[from..]` according to the [MatchKind],
+    /// as `(start, end, node)`.
+    fn find_at(&self, text: &[Label], from: usize) -> Option<(usize, usize, u64)> {
+        let mut search = Search::new();
+        for (i, label) in text.iter().enumerate().skip(from) {
+            if let Some(found) = self.step(&mut search, label, i + 1) {
+                return Some(found);
+            }
+        }
+        search.best
+    }
+
+    /// Feed `search` the label ending at offset `end`. Return the match once
+    /// no later label can change it; the search must then restart at its end.
+    fn step(&self, search: &mut Search, label: &Label, end: usize) -> Option<(usize, usize, u64)> {
+        search.state = self.next_state(search.state, label);
+
+        // Stop once no match through `state` can start early enough to beat
+        // the best one.
+        let reach = end - self.depth(search.state);
+        let beaten = match (search.best, self.kind) {
+            (None, _) => false,
+            (Some((start, _, _)), MatchKind::LeftmostLongest) => reach > start,
+            // A longer key from the same start only wins if it comes first.
+            (Some((start, _, best)), MatchKind::LeftmostFirst) => {
+                reach > start
+                    || (reach == start && self.first_order(search.state) > self.order(best))
+            }
+            (Some((start, _, _)), _) => reach >= start,
+        };
+        if beaten {
+            return search.best;
+        }
+
+        let node = self.first_output(search.state);
+        if node == 0 {
+            return None;
+        }
+        let start = end - self.depth(node);
+        match (search.best, self.kind) {
+            (_, MatchKind::Standard) => return Some((start, end, node)),
+            (Some((best_start, _, best)), MatchKind::LeftmostFirst)
+                if start > best_start
+                    || (start == best_start && self.order(node) > self.order(best)) => {}
+            (Some((best_start, _, _)), _) if start > best_start => {}
+            _ => search.best = Some((start, end, node)),
+        }
+        None
+    }
+
+    /// Return a [StreamScanner] that finds the non-overlapping matches picked
+    /// by the [MatchKind] in a text fed in chunks.
+    pub fn stream(&self) -> StreamScanner<'_, 'a, Label, Value> {
+        StreamScanner::new(self, false)
+    }
+
+    /// Return a [StreamScanner] that finds every match in a text fed in
+    /// chunks, including overlapping ones.
+    pub fn stream_overlapping(&self) -> StreamScanner<'_, 'a, Label, Value> {
+        StreamScanner::new(self, true)
+    }
+
+    fn value(&self, node: u64) -> &'a Value {
+        self.trie.value(LoudsNodeNum(node)).expect("Not a terminal")
+    }
+}
+
+/// Return the state after reading `label` in `state`, following the failure
+/// links given by `fail`.
+fn next_state<Label: Ord, Value>(
+    trie: &Trie<Label, Value>,
+    fail: impl Fn(u64) -> u64,
+    mut state: u64,
+    label: &Label,
+) -> u64 {
+    loop {
+        if let Some(child) = trie.child(LoudsNodeNum(state), label) {
+            return child.0;
+        }
+        if state == ROOT {
+            return ROOT;
+        }
+        state = fail(state);
+    }
+}
+
+/// A search for the next non-overlapping match, fed one label at a time.
+#[derive(Debug, Clone)]
+struct Search {
+    state: u64,
+    /// The best match so far, as `(start, end, node)`.
+    best: Option<(usize, usize, u64)>,
+}
+
+impl Search {
+    fn new() -> Self {
+        Self {
+            state: ROOT,
+            best: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Finds the matches of an [AhoCorasick] matcher in a text fed in chunks, such
+/// as the reads from a [std::io::Read].
+///
+/// Offsets count from the start of the stream, and matches may straddle
+/// chunks. Deciding on a non-overlapping match can take labels from the next
+/// chunks, so [StreamScanner::finish] reports the matches left at the end of
+/// the stream. The scanner holds on to at most as many labels as the longest
+/// key.
+///
+/// ```rust
+/// use std::io::Read;
+/// use trie::map::Trie;
+/// use trie::aho_corasick::MatchKind;
+///
+/// let trie = Trie::from_iter([("error", 0), ("warn", 1)].map(|(k, v)| (k.bytes(), v)));
+/// let ac = trie.aho_corasick(MatchKind::LeftmostLongest);
+/// let mut scanner = ac.stream();
+///
+/// let mut reader = "ok\nwarning\nerror\n".as_bytes();
+/// let mut chunk = [0; 4];
+/// let mut matches = Vec::new();
+/// loop {
+///     let n = reader.read(&mut chunk).unwrap();
+///     if n == 0 {
+///         break;
+///     }
+///     matches.extend(scanner.feed(&chunk[..n]));
+/// }
+/// matches.extend(scanner.finish());
+/// assert_eq!(matches, [(3, 7, &1), (11, 16, &0)]);
+/// ```
+pub struct StreamScanner<'s, 'a, Label, Value> {
+    ac: &'s AhoCorasick<'a, Label, Value>,
+    overlapping: bool,
+    search: Search,
+    /// The labels from offset `offset` on, which a restarted search may read
+    /// again. Empty when overlapping.
+    buffer: VecDeque<Label>,
+    offset: usize,
+    /// The number of labels in `buffer` the search has read.
+    read: usize,
+}
+
+impl<'s, 'a, Label, Value> StreamScanner<'s, 'a, Label, Value> {
+    fn new(ac: &'s AhoCorasick<'a, Label, Value>, overlapping: bool) -> Self {
+        Self {
+            ac,
+            overlapping,
+            search: Search::new(),
+            buffer: VecDeque::new(),
+            offset: 0,
+            read: 0,
+        }
+    }
+}
+
+impl<'s, 'a, Label: Ord + Clone, Value> StreamScanner<'s, 'a, Label, Value> {
+    /// Scan the next chunk of the stream and return the matches decided so
+    /// far, as `(start, end, &Value)`.
+    pub fn feed(&mut self, chunk: &[Label]) -> Vec<(usize, usize, &'a Value)> {
+        let mut matches = Vec::new();
+        if self.overlapping {
+            for label in chunk {
+                self.search.state = self.ac.next_state(self.search.state, label);
+                self.offset += 1;
+                let mut node = self.ac.first_output(self.search.state);
+                while node != 0 {
+                    matches.push(self.found(node, self.offset));
+                    node = self.ac.output(node);
+                }
+            }
+            return matches;
+        }
+
+        self.buffer.extend(chunk.iter().cloned());
+        self.scan(&mut matches);
+        matches
+    }
+
+    /// End the stream and return the matches that were still pending.
+    pub fn finish(mut self) -> Vec<(usize, usize, &'a Value)> {
+        let mut matches = Vec::new();
+        while let Some((_, end, node)) = self.search.best.take() {
+            matches.push(self.found(node, end));
+            self.restart(end);
+            self.scan(&mut matches);
+        }
+        matches
+    }
+
+    /// Run the search over the unread labels of the buffer.
+    fn scan(&mut self, matches: &mut Vec<(usize, usize, &'a Value)>) {
+        while self.read < self.buffer.len() {
+            let end = self.offset + self.read + 1;
+            let found = self.ac.step(&mut self.search, &self.buffer[self.read], end);
+            self.read += 1;
+            match found {
+                Some((_, end, node)) => {
+                    matches.push(self.found(node, end));
+                    self.restart(end);
+                }
+                None => {
+                    // Drop the labels no restarted search will read.
+                    let keep = match self.search.best {
+                        Some((_, end, _)) => end,
+                        None => end - self.ac.depth(self.search.state),
+                    };
+                    self.drop_before(keep);
+                }
+            }
+        }
+    }
+
+    /// Start a new search at offset `from`.
+    fn restart(&mut self, from: usize) {
+        self.search = Search::new();
+        self.drop_before(from);
+        self.read = 0;
+    }
+
+    fn drop_before(&mut self, offset: usize) {
+        let n = offset - self.offset;
+        self.buffer.drain(..n);
+        self.read -= n.min(self.read);
+        self.offset = offset;
+    }
+
+    fn found(&self, node: u64, end: usize) -> (usize, usize, &'a Value) {
+        let start = end - self.ac.depth(node);
+        (start, end, self.ac.value(node))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Iterates through all the matches of an [AhoCorasick] matcher in a text,
+/// including overlapping ones.
+pub struct FindOverlappingIter<'s, 'a, Label, Value> {
+    ac: &'s AhoCorasick<'a, Label, Value>,
+    text: &'s [Label],
+    end: usize,
+    state: u64,
+    /// The next match ending at `end` to report, or 0 if there is none.
+    out: u64,
+}
+
+impl<'s, 'a, Label: Ord, Value> Iterator for FindOverlappingIter<'s, 'a, Label, Value> {
+    type Item = (usize, usize, &'a Value);
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.out == 0 {
+            let label = self.text.get(self.end)?;
+            self.state = self.ac.next_state(self.state, label);
+            self.end += 1;
+            self.out = self.ac.first_output(self.state);
+        }
+        let node = self.out;
+        self.out = self.ac.output(node);
+        let start = self.end - self.ac.depth(node);
+        Some((start, self.end, self.ac.value(node)))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Iterates through the non-overlapping matches of an [AhoCorasick] matcher in
+/// a text.
+pub struct FindIter<'s, 'a, Label, Value> {
+    ac: &'s AhoCorasick<'a, Label, Value>,
+    text: &'s [Label],
+    pos: usize,
+}
+
+impl<'s, 'a, Label: Ord, Value> Iterator for FindIter<'s, 'a, Label, Value> {
+    type Item = (usize, usize, &'a Value);
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, end, node) = self.ac.find_at(self.text, self.pos)?;
+        self.pos = end;
+        Some((start, end, self.ac.value(node)))
+    }
+}
+
+#[cfg(test)]
+mod search_tests {
+    use super::MatchKind;
+    use crate::map::{BuildOptions, Trie, TrieBuilder};
+
+    fn build_trie() -> Trie<u8, u8> {
+        Trie::from_iter(
+            [
+                ("he", 0),
+                ("she", 1),
+                ("his", 2),
+                ("hers", 3),
+                ("a", 4),
+                ("abcd", 5),
+                ("bc", 6),
+            ]
+            .map(|(k, v)| (k.bytes(), v)),
+        )
+    }
+
+    /// Every match, by checking every key at every offset.
+    fn naive_matches<'a>(trie: &'a Trie<u8, u8>, text: &str) -> Vec<(usize, usize, &'a u8)> {
+        let text = text.as_bytes();
+        let mut matches: Vec<_> = (0..text.len())
+            .flat_map(|start| {
+                trie.common_prefix_search::<Vec<u8>, _>(&text[start..])
+                    .map(move |(key, v)| (start, start + key.len(), v))
+            })
+            .collect();
+        matches.sort_by_key(|&(start, end, _)| (end, start));
+        matches
+    }
+
+    mod find_overlapping_tests {
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let text = $value;
+                    let trie = super::build_trie();
+                    let ac = trie.aho_corasick(super::MatchKind::Standard);
+                    let matches: Vec<_> = ac.find_overlapping_iter(text).collect();
+                    assert_eq!(matches, super::naive_matches(&trie, text));
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: "",
+            t2: "ushers",
+            t3: "hishers",
+            t4: "abcd",
+            t5: "abcabcdbc",
+            t6: "xyz",
+            t7: "aaaa",
+            t8: "shershe",
+        }
+    }
+
+    mod find_tests {
+        use super::MatchKind::{self, LeftmostFirst, LeftmostLongest, Standard};
+
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (kind, text, expected): (MatchKind, &str, Vec<(usize, usize, u8)>) = $value;
+                    let trie = super::build_trie();
+                    let ac = trie.aho_corasick(kind);
+                    let matches: Vec<_> = ac.find_iter(text).map(|(s, e, v)| (s, e, *v)).collect();
+                    assert_eq!(matches, expected);
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: (Standard, "ushers", vec![(1, 4, 1)]),
+            t2: (LeftmostFirst, "ushers", vec![(1, 4, 1)]),
+            t3: (LeftmostLongest, "ushers", vec![(1, 4, 1)]),
+            t4: (Standard, "abcd", vec![(0, 1, 4), (1, 3, 6)]),
+            t5: (LeftmostFirst, "abcd", vec![(0, 1, 4), (1, 3, 6)]),
+            t6: (LeftmostLongest, "abcd", vec![(0, 4, 5)]),
+            t7: (Standard, "hers", vec![(0, 2, 0)]),
+            t8: (LeftmostLongest, "hers", vec![(0, 4, 3)]),
+            t9: (LeftmostLongest, "xabcx abc", vec![(1, 2, 4), (2, 4, 6), (6, 7, 4), (7, 9, 6)]),
+            t10: (LeftmostLongest, "", vec![]),
+            t11: (Standard, "zzz", vec![]),
+            t12: (LeftmostFirst, "hers", vec![(0, 2, 0)]),
+        }
+    }
+
+    #[test]
+    fn leftmost_prefers_earlier_start() {
+        // "bc" ends before "abcd" does, but "abcd" starts first.
+        let trie = Trie::from_iter([("abcd", 0), ("bc", 1)].map(|(k, v)| (k.bytes(), v)));
+        let ac = trie.aho_corasick(MatchKind::LeftmostFirst);
+        let matches: Vec<_> = ac.find_iter("abcd").collect();
+        assert_eq!(matches, [(0, 4, &0)]);
+
+        let ac = trie.aho_corasick(MatchKind::Standard);
+        let matches: Vec<_> = ac.find_iter("abcd").collect();
+        assert_eq!(matches, [(1, 3, &1)]);
+
+        // An unfinished longer key must not hide a match.
+        let matches: Vec<_> = ac.find_iter("abcbc").collect();
+        assert_eq!(matches, [(1, 3, &1), (3, 5, &1)]);
+        let ac = trie.aho_corasick(MatchKind::LeftmostLongest);
+        let matches: Vec<_> = ac.find_iter("abcbc").collect();
+        assert_eq!(matches, [(1, 3, &1), (3, 5, &1)]);
+    }
+
+    #[test]
+    fn leftmost_first() {
+        let build = |keys: &[&str]| {
+            let mut builder = TrieBuilder::new();
+            for (i, key) in keys.iter().enumerate() {
+                builder.insert(key.bytes(), i);
+            }
+            builder.build_with(BuildOptions::new().insertion_order())
+        };
+        let trie = build(&["Samwise", "Sam"]);
+        let ac = trie.aho_corasick(MatchKind::LeftmostFirst);
+        assert_eq!(ac.find_iter("Samwise").collect::<Vec<_>>(), [(0, 7, &0)]);
+        assert_eq!(ac.find_iter("Samwis").collect::<Vec<_>>(), [(0, 3, &1)]);
+        let trie = build(&["Sam", "Samwise"]);
+        let ac = trie.aho_corasick(MatchKind::LeftmostFirst);
+        assert_eq!(ac.find_iter("Samwise").collect::<Vec<_>>(), [(0, 3, &0)]);
+
+        // Without the insertion order, the shortest key wins.
+        let trie = Trie::from_iter([("Samwise", 0), ("Sam", 1)].map(|(k, v)| (k.bytes(), v)));
+        let ac = trie.aho_corasick(MatchKind::LeftmostFirst);
+        assert_eq!(ac.find_iter("Samwise").collect::<Vec<_>>(), [(0, 3, &1)]);
+    }
+
+    #[test]
+    fn leftmost_first_matches_naive() {
+        /// The non-overlapping matches, by trying every key at every offset.
+        fn naive(trie: &Trie<u8, usize>, text: &[u8]) -> Vec<(usize, usize, usize)> {
+            let mut matches = Vec::new();
+            let mut start = 0;
+            while start < text.len() {
+                let first = trie
+                    .common_prefix_search::<Vec<u8>, _>(&text[start..])
+                    .min_by_key(|(_, &order)| order);
+                match first {
+                    Some((key, &order)) => {
+                        matches.push((start, start + key.len(), order));
+                        start += key.len().max(1);
+                    }
+                    None => start += 1,
+                }
+            }
+            matches
+        }
+
+        let orders: [&[&str]; 3] = [
+            &["hers", "he", "she", "his", "abcd", "a", "bc", "b"],
+            &["a", "b", "bc", "abcd", "his", "she", "he", "hers"],
+            &["bc", "abcd", "hers", "a", "she", "b", "he", "his"],
+        ];
+        let texts = [
+            "ushers",
+            "hishers",
+            "abcabcdbc",
+            "xabcx abc",
+            "abcbc",
+            "aaaa",
+            "hehers",
+        ];
+        for keys in orders {
+            let mut builder = TrieBuilder::new();
+            for (i, key) in keys.iter().enumerate() {
+                builder.insert(key.bytes(), i);
+            }
+            let trie = builder.build_with(BuildOptions::new().insertion_order());
+            let ac = trie.aho_corasick(MatchKind::LeftmostFirst);
+            for text in texts {
+                let text = text.as_bytes();
+                let matches: Vec<_> = ac.find_iter(text).map(|(s, e, v)| (s, e, *v)).collect();
+                assert_eq!(matches, naive(&trie, text), "{:?} in {:?}", keys, text);
+
+                let mut scanner = ac.stream();
+                let mut streamed = Vec::new();
+                for chunk in text.chunks(2) {
+                    streamed.extend(scanner.feed(chunk));
+                }
+                streamed.extend(scanner.finish());
+                let streamed: Vec<_> = streamed.into_iter().map(|(s, e, v)| (s, e, *v)).collect();
+                assert_eq!(streamed, matches);
+            }
+        }
+    }
+
+    #[test]
+    fn stream() {
+        use MatchKind::{LeftmostFirst, LeftmostLongest, Standard};
+
+        let trie = build_trie();
+        let texts = [
+            "ushers",
+            "hishers",
+            "abcabcdbc",
+            "xabcx abc",
+            "abcbc",
+            "aaaa",
+            "",
+        ];
+        for kind in [Standard, LeftmostFirst, LeftmostLongest] {
+            let ac = trie.aho_corasick(kind);
+            for text in texts {
+                let text = text.as_bytes();
+                let expected: Vec<_> = ac.find_iter(text).collect();
+                let expected_overlapping: Vec<_> = ac.find_overlapping_iter(text).collect();
+                for chunk_len in 1..=text.len().max(1) {
+                    let mut scanner = ac.stream();
+                    let mut overlapping = ac.stream_overlapping();
+                    let mut matches = Vec::new();
+                    let mut matches_overlapping = Vec::new();
+                    for chunk in text.chunks(chunk_len) {
+                        matches.extend(scanner.feed(chunk));
+                        matches_overlapping.extend(overlapping.feed(chunk));
+                    }
+                    matches.extend(scanner.finish());
+                    matches_overlapping.extend(overlapping.finish());
+                    assert_eq!(matches, expected);
+                    assert_eq!(matches_overlapping, expected_overlapping);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stream_buffer_is_bounded() {
+        let trie = build_trie();
+        let ac = trie.aho_corasick(MatchKind::LeftmostLongest);
+        let mut scanner = ac.stream();
+        let mut count = 0;
+        for _ in 0..1000 {
+            count += scanner.feed(b"xyzabcdhe").len();
+            assert!(scanner.buffer.len() <= 4);
+        }
+        count += scanner.finish().len();
+        assert_eq!(count, 2000);
+    }
+
+    #[test]
+    fn replace_all() {
+        let trie: Trie<u8, &str> = Trie::from_iter(
+            [("cat", "dog"), ("category", "kind"), ("a", "an")].map(|(k, v)| (k.bytes(), v)),
+        );
+        let ac = trie.aho_corasick(MatchKind::LeftmostLongest);
+        assert_eq!(ac.replace_all("a cat category"), b"an dog kind");
+        assert_eq!(ac.replace_all(""), b"");
+        let ac = trie.aho_corasick(MatchKind::LeftmostFirst);
+        assert_eq!(ac.replace_all("a cat category"), b"an dog dogegory");
+    }
+
+    #[test]
+    fn set_trie() {
+        let trie = crate::Trie::from_iter(["he", "she"].map(|k| k.bytes()));
+        let ac = trie.aho_corasick(MatchKind::Standard);
+        let matches: Vec<_> = ac
+            .find_overlapping_iter("she")
+            .map(|m| (m.0, m.1))
+            .collect();
+        assert_eq!(matches, [(0, 3), (1, 3)]);
+    }
+}
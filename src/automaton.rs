@@ -0,0 +1,221 @@
+//! Automata for driving trie traversal
+//!
+//! An [Automaton] consumes one `Label` at a time. Intersecting it with a trie
+//! through [`search_automaton()`][crate::map::Trie::search_automaton] yields
+//! every entry the automaton matches, and skips every subtree where
+//! [Automaton::can_match] returns false.
+//!
+//! ```rust
+//! use trie::Trie;
+//! use trie::automaton::Automaton;
+//!
+//! /// Matches keys with an even number of labels.
+//! struct EvenLength;
+//!
+//! impl Automaton<u8> for EvenLength {
+//!     type State = bool;
+//!
+//!     fn start(&self) -> bool {
+//!         true
+//!     }
+//!
+//!     fn accept(&self, state: &bool, _label: &u8) -> bool {
+//!         !state
+//!     }
+//!
+//!     fn is_match(&self, state: &bool) -> bool {
+//!         *state
+//!     }
+//! }
+//!
+//! let trie = Trie::from_iter(["a", "ab", "abc", "abcd"].into_iter().map(|s| s.bytes()));
+//! let results: Vec<String> = trie.search_automaton(EvenLength).collect();
+//! assert_eq!(results, ["ab", "abcd"]);
+//! ```
+//!
+//! A few automata are provided: [Str], [Levenshtein] and the [StartsWith]
+//! combinator.
+
+/// A deterministic automaton over `Label`s.
+pub trait Automaton<Label> {
+    /// The type of the automaton's state.
+    type State: Clone;
+
+    /// Return the start state.
+    fn start(&self) -> Self::State;
+
+    /// Return the state after consuming `label` in `state`.
+    fn accept(&self, state: &Self::State, label: &Label) -> Self::State;
+
+    /// Does `state` accept the labels consumed so far?
+    fn is_match(&self, state: &Self::State) -> bool;
+
+    /// Can `state` still reach a match by consuming more labels?
+    ///
+    /// Returning false lets searches skip a whole subtree. It must not return
+    /// false for a state from which a match is reachable.
+    fn can_match(&self, _state: &Self::State) -> bool {
+        true
+    }
+}
+
+impl<Label, A: Automaton<Label>> Automaton<Label> for &A {
+    type State = A::State;
+
+    fn start(&self) -> Self::State {
+        (**self).start()
+    }
+
+    fn accept(&self, state: &Self::State, label: &Label) -> Self::State {
+        (**self).accept(state, label)
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        (**self).is_match(state)
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        (**self).can_match(state)
+    }
+}
+
+/// Matches exactly one sequence of labels.
+#[derive(Debug, Clone)]
+pub struct Str<Label>(Vec<Label>);
+
+impl<Label: Clone> Str<Label> {
+    /// Create an automaton matching `query` exactly.
+    pub fn new(query: impl AsRef<[Label]>) -> Self {
+        Self(query.as_ref().to_vec())
+    }
+}
+
+impl<Label: Eq> Automaton<Label> for Str<Label> {
+    /// Number of labels matched so far, or `None` on mismatch.
+    type State = Option<usize>;
+
+    fn start(&self) -> Self::State {
+        Some(0)
+    }
+
+    fn accept(&self, state: &Self::State, label: &Label) -> Self::State {
+        state
+            .filter(|&i| self.0.get(i) == Some(label))
+            .map(|i| i + 1)
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        *state == Some(self.0.len())
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        state.is_some()
+    }
+}
+
+/// Matches sequences within a Levenshtein distance of a query.
+#[derive(Debug, Clone)]
+pub struct Levenshtein<Label> {
+    query: Vec<Label>,
+    max_edits: usize,
+}
+
+impl<Label: Clone> Levenshtein<Label> {
+    /// Create an automaton matching sequences within `max_edits` edits of
+    /// `query`.
+    pub fn new(query: impl AsRef<[Label]>, max_edits: usize) -> Self {
+        Self {
+            query: query.as_ref().to_vec(),
+            max_edits,
+        }
+    }
+}
+
+impl<Label: Eq> Automaton<Label> for Levenshtein<Label> {
+    /// The edit distances between the labels consumed so far and each prefix
+    /// of the query.
+    type State = Vec<usize>;
+
+    fn start(&self) -> Self::State {
+        (0..=self.query.len()).collect()
+    }
+
+    fn accept(&self, state: &Self::State, label: &Label) -> Self::State {
+        next_row(state, &self.query, label)
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        state[self.query.len()] <= self.max_edits
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        state.iter().any(|&d| d <= self.max_edits)
+    }
+}
+
+/// Compute the Levenshtein row for `prefix + label` from the row of `prefix`.
+pub(crate) fn next_row<Label: Eq>(row: &[usize], query: &[Label], label: &Label) -> Vec<usize> {
+    let mut next = Vec::with_capacity(row.len());
+    next.push(row[0] + 1);
+    for (j, chr) in query.iter().enumerate() {
+        let substitute = row[j] + usize::from(chr != label);
+        let insert = next[j] + 1;
+        let delete = row[j + 1] + 1;
+        next.push(substitute.min(insert).min(delete));
+    }
+    next
+}
+
+/// Matches every sequence that has a prefix matched by the inner automaton.
+///
+/// ```rust
+/// use trie::Trie;
+/// use trie::automaton::{Levenshtein, StartsWith};
+///
+/// let trie = Trie::from_iter(["apple", "application", "banana"].into_iter().map(|s| s.bytes()));
+/// let results: Vec<String> = trie
+///     .search_automaton(StartsWith(Levenshtein::new("aple", 1)))
+///     .collect();
+/// assert_eq!(results, ["apple"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StartsWith<A>(pub A);
+
+impl<Label, A: Automaton<Label>> Automaton<Label> for StartsWith<A> {
+    /// `Ok` while no prefix has matched yet, then `Err(())` forever.
+    type State = Result<A::State, ()>;
+
+    fn start(&self) -> Self::State {
+        let state = self.0.start();
+        if self.0.is_match(&state) {
+            Err(())
+        } else {
+            Ok(state)
+        }
+    }
+
+    fn accept(&self, state: &Self::State, label: &Label) -> Self::State {
+        match state {
+            Ok(state) => {
+                let next = self.0.accept(state, label);
+                if self.0.is_match(&next) {
+                    Err(())
+                } else {
+                    Ok(next)
+                }
+            }
+            Err(()) => Err(()),
+        }
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        state.is_err()
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        match state {
+            Ok(state) => self.0.can_match(state),
+            Err(()) => true,
+        }
+    }
+}
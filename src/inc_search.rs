@@ -39,8 +39,18 @@
 //! This means the above code restores the time complexity of _O(m log n)_ for
 //! the loop.
 
-use crate::{map::Trie, try_collect::TryCollect, try_collect::TryFromIterator};
-use louds::LoudsNodeNum;
+use crate::{
+    internal_data_structure::fnv::Fnv1a,
+    iter::{ArcSearchIter, SearchIter},
+    map::Trie,
+    node_ref::NodeRef,
+    try_collect::TryFromIterator,
+};
+use fid::BitVector;
+use louds::{ChildNodeIter, LoudsNodeNum};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 /// An incremental search of the trie.
@@ -55,13 +65,78 @@ pub struct IncSearch<'a, Label, Value> {
 ///
 /// "Position" is more descriptive for incremental search purposes, and without
 /// it a user would have to explicitly depend on `louds-rs`.
-pub type Position = LoudsNodeNum;
+///
+/// It also records a fingerprint of the shape of the trie it comes from, and,
+/// if it comes from [IncSearch::position], a hash of the key it is at, so that
+/// [IncSearch::resume_checked] can turn down a position that would not resume
+/// at the same key. With the `serde` feature it can be serialized, to carry a
+/// search across requests.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    node: u64,
+    fingerprint: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    key: Option<u64>,
+}
 
 /// Retrieve the position the search is on. Useful for hanging on to a search
 /// without having to fight the borrow checker because its borrowing a trie.
+///
+/// Unlike [IncSearch::position], this does not hash the key, so it does not
+/// need `L: Hash`, and [IncSearch::resume_checked] only checks the shape of the
+/// trie for it.
 impl<'a, L, V> From<IncSearch<'a, L, V>> for Position {
     fn from(inc_search: IncSearch<'a, L, V>) -> Self {
-        inc_search.node
+        Position {
+            node: inc_search.node.0,
+            fingerprint: inc_search.trie.fingerprint(),
+            key: None,
+        }
+    }
+}
+
+/// The reason [IncSearch::resume_checked] turned down a [Position].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PositionError {
+    /// The position is past the last node of the trie.
+    OutOfRange,
+    /// The position comes from a trie of another shape, or it is at another
+    /// key in this one.
+    WrongTrie,
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::OutOfRange => write!(f, "position is out of range of the trie"),
+            PositionError::WrongTrie => write!(f, "position belongs to a different trie"),
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+impl Position {
+    /// Return the position of `node` in `trie`, with the hash of its key.
+    fn new<Label: Ord + Hash, Value>(trie: &Trie<Label, Value>, node: LoudsNodeNum) -> Self {
+        Self {
+            node: node.0,
+            fingerprint: trie.fingerprint(),
+            key: Some(Self::key_hash(trie, node)),
+        }
+    }
+
+    /// Hash the labels of the key leading to `node`. Walking up from `node`
+    /// only takes time proportional to the length of the key, and a position
+    /// that hashes the same leads to the same key, whatever the rest of the
+    /// labels are.
+    fn key_hash<Label: Ord + Hash, Value>(trie: &Trie<Label, Value>, node: LoudsNodeNum) -> u64 {
+        let mut hasher = Fnv1a::default();
+        for ancestor in trie.child_to_ancestors(node) {
+            trie.label(ancestor).hash(&mut hasher);
+        }
+        hasher.finish()
     }
 }
 
@@ -108,9 +183,12 @@ impl<'a, Label: Ord, Value> IncSearch<'a, Label, Value> {
 
     /// Resume an incremental search at a particular point.
     ///
+    /// The position is trusted to come from `trie`. Use
+    /// [resume_checked()][IncSearch::resume_checked] for positions that may
+    /// not.
+    ///
     /// ```
-    /// use trie::{Trie, inc_search::{Answer, IncSearch}};
-    /// use louds::LoudsNodeNum;
+    /// use trie::{Trie, inc_search::{Answer, IncSearch, Position}};
     ///
     /// let trie: Trie<u8> = ["hello", "bye"]
     ///     .into_iter()
@@ -119,7 +197,7 @@ impl<'a, Label: Ord, Value> IncSearch<'a, Label, Value> {
     /// let mut inc_search = trie.inc_search();
     ///
     /// assert_eq!(inc_search.query_until("he"), Ok(Answer::Prefix));
-    /// let position = LoudsNodeNum::from(inc_search);
+    /// let position = Position::from(inc_search);
     ///
     /// // inc_search is dropped.
     /// let mut inc_search2 = IncSearch::resume(&trie.0, position);
@@ -129,42 +207,73 @@ impl<'a, Label: Ord, Value> IncSearch<'a, Label, Value> {
     pub fn resume(trie: &'a Trie<Label, Value>, position: Position) -> Self {
         Self {
             trie,
-            node: position,
+            node: LoudsNodeNum(position.node),
         }
     }
 
-    /// Query but do not change the node we're looking at on the trie.
-    pub fn peek(&self, chr: &Label) -> Option<Answer> {
-        let children_node_nums: Vec<_> = self.trie.children_node_nums(self.node).collect();
-        let res = self
-            .trie
-            .bin_search_by_children_labels(chr, &children_node_nums[..]);
-        match res {
-            Ok(j) => {
-                let node = children_node_nums[j];
-                let is_prefix = self.trie.has_children_node_nums(node);
-                let is_match = self.trie.value(node).is_some();
-                Answer::new(is_prefix, is_match)
+    /// Resume an incremental search at a particular point, if the position
+    /// comes from a trie of the same shape as `trie`, and, for one from
+    /// [position()][IncSearch::position], is at the same key in it.
+    ///
+    /// ```
+    /// use trie::{Trie, inc_search::{Answer, IncSearch, PositionError}};
+    ///
+    /// let trie: Trie<u8> = ["hello", "bye"].into_iter().map(|s| s.bytes()).collect();
+    /// let other: Trie<u8> = ["help"].into_iter().map(|s| s.bytes()).collect();
+    /// let mut inc_search = trie.inc_search();
+    /// inc_search.query_until("he").unwrap();
+    /// let position = inc_search.position();
+    ///
+    /// let mut inc_search = IncSearch::resume_checked(&trie.0, position).unwrap();
+    /// assert_eq!(inc_search.query_until("llo"), Ok(Answer::Match));
+    /// assert_eq!(
+    ///     IncSearch::resume_checked(&other.0, position).err(),
+    ///     Some(PositionError::WrongTrie)
+    /// );
+    /// ```
+    pub fn resume_checked(
+        trie: &'a Trie<Label, Value>,
+        position: Position,
+    ) -> Result<Self, PositionError>
+    where
+        Label: Hash,
+    {
+        if position.node == 0 || position.node as usize > trie.num_nodes() {
+            return Err(PositionError::OutOfRange);
+        }
+        if position.fingerprint != trie.fingerprint() {
+            return Err(PositionError::WrongTrie);
+        }
+        if let Some(key) = position.key {
+            if key != Position::key_hash(trie, LoudsNodeNum(position.node)) {
+                return Err(PositionError::WrongTrie);
             }
-            Err(_) => None,
         }
+        Ok(Self::resume(trie, position))
+    }
+
+    /// Return the position the search is on, to resume it later.
+    pub fn position(&self) -> Position
+    where
+        Label: Hash,
+    {
+        Position::new(self.trie, self.node)
+    }
+
+    /// Query but do not change the node we're looking at on the trie.
+    pub fn peek(&self, chr: &Label) -> Option<Answer> {
+        let node = self.trie.child(self.node, chr)?;
+        let is_prefix = self.trie.has_children_node_nums(node);
+        let is_match = self.trie.value(node).is_some();
+        Answer::new(is_prefix, is_match)
     }
 
     /// Query the trie and go to node if there is a match.
     pub fn query(&mut self, chr: &Label) -> Option<Answer> {
-        let children_node_nums: Vec<_> = self.trie.children_node_nums(self.node).collect();
-        let res = self
-            .trie
-            .bin_search_by_children_labels(chr, &children_node_nums[..]);
-        match res {
-            Ok(j) => {
-                self.node = children_node_nums[j];
-                let is_prefix = self.trie.has_children_node_nums(self.node);
-                let is_match = self.trie.value(self.node).is_some();
-                Answer::new(is_prefix, is_match)
-            }
-            Err(_) => None,
-        }
+        self.node = self.trie.child(self.node, chr)?;
+        let is_prefix = self.trie.has_children_node_nums(self.node);
+        let is_match = self.trie.value(self.node).is_some();
+        Answer::new(is_prefix, is_match)
     }
 
     /// Query the trie with a sequence. Will return `Err(index of query)` on
@@ -182,6 +291,72 @@ impl<'a, Label: Ord, Value> IncSearch<'a, Label, Value> {
         result.ok_or(i)
     }
 
+    /// Follow `query` from the current position and go to the longest key
+    /// along it. Return the number of labels of `query` that takes, and the
+    /// key's value, or `None` without moving if there is no key along it.
+    ///
+    /// ```
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Trie::from_iter([("app", 1), ("apple", 2)].map(|(k, v)| (k.bytes(), v)));
+    /// let mut inc_search = trie.inc_search();
+    /// assert_eq!(inc_search.query_longest("applause"), Some((3, &1)));
+    /// assert_eq!(inc_search.prefix::<String, _>(), "app");
+    /// assert_eq!(inc_search.query_longest("lesson"), Some((2, &2)));
+    /// assert_eq!(inc_search.prefix::<String, _>(), "apple");
+    /// ```
+    pub fn query_longest(&mut self, query: impl AsRef<[Label]>) -> Option<(usize, &'a Value)> {
+        let (len, node) = self.trie.longest_match_node(self.node, query.as_ref())?;
+        self.node = node;
+        Some((len, self.value().expect("Not a terminal")))
+    }
+
+    /// Return the node the search is on. See [crate::node_ref].
+    pub fn node(&self) -> NodeRef<'a, Label, Value> {
+        NodeRef::new(self.trie, self.node)
+    }
+
+    /// Iterate over the labels that can follow the current prefix, in order,
+    /// with what querying each of them would answer.
+    ///
+    /// ```
+    /// use trie::{Trie, inc_search::Answer};
+    ///
+    /// let trie: Trie<u8> = ["a", "app", "apple", "apt"].into_iter().map(|s| s.bytes()).collect();
+    /// let mut inc_search = trie.inc_search();
+    /// inc_search.query_until("ap").unwrap();
+    /// let next: Vec<(&u8, Answer)> = inc_search.next_labels().collect();
+    /// assert_eq!(next, [(&b'p', Answer::PrefixAndMatch), (&b't', Answer::Match)]);
+    /// ```
+    pub fn next_labels(&self) -> NextLabels<'a, Label, Value> {
+        NextLabels {
+            trie: self.trie,
+            children: self.trie.children_node_nums(self.node),
+        }
+    }
+
+    /// Iterate over the entries that start with the current prefix, the
+    /// prefix itself included, like
+    /// [predictive_search()][Trie::predictive_search] but without following
+    /// the prefix from the root again.
+    ///
+    /// ```
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Trie::from_iter([("app", 1), ("apple", 2), ("bet", 3)].map(|(k, v)| (k.bytes(), v)));
+    /// let mut inc_search = trie.inc_search();
+    /// inc_search.query_until("ap").unwrap();
+    /// let completions: Vec<(String, &i32)> = inc_search.completions().collect();
+    /// assert_eq!(completions, [("app".into(), &1), ("apple".into(), &2)]);
+    /// ```
+    pub fn completions<C, M>(&self) -> SearchIter<'a, Label, Value, C, M>
+    where
+        C: TryFromIterator<Label, M> + Clone,
+        Label: Clone,
+    {
+        SearchIter::at_node(self.trie, self.node, self.trie.key(self.node))
+    }
+
     /// Return the value at current node. There should be one for any node where
     /// `answer.is_match()` is true.
     pub fn value(&self) -> Option<&'a Value> {
@@ -219,13 +394,7 @@ impl<'a, Label: Ord, Value> IncSearch<'a, Label, Value> {
         C: TryFromIterator<Label, M>,
         Label: Clone,
     {
-        let mut v: Vec<Label> = self
-            .trie
-            .child_to_ancestors(self.node)
-            .map(|node| self.trie.label(node).clone())
-            .collect();
-        v.reverse();
-        v.into_iter().try_collect().expect("Could not collect")
+        self.trie.key(self.node)
     }
 
     /// Returne the length of the current prefix for this search.
@@ -256,12 +425,203 @@ impl<'a, Label: Ord, Value> IncSearch<'a, Label, Value> {
     //     trie.value_mut(self.node)
     // }
 
+    /// Go back one label and return it, or `None` at the root.
+    ///
+    /// ```
+    /// use trie::Trie;
+    ///
+    /// let trie: Trie<u8> = ["hello", "help"].into_iter().map(|s| s.bytes()).collect();
+    /// let mut inc_search = trie.inc_search();
+    /// inc_search.query_until("hell").unwrap();
+    /// assert_eq!(inc_search.pop(), Some(&b'l'));
+    /// assert_eq!(inc_search.prefix::<String, _>(), "hel");
+    /// inc_search.query(&b'p').unwrap();
+    /// inc_search.truncate(1);
+    /// assert_eq!(inc_search.prefix::<String, _>(), "h");
+    /// ```
+    pub fn pop(&mut self) -> Option<&'a Label> {
+        let parent = self.trie.parent_node(self.node)?;
+        let label = self.trie.label(self.node);
+        self.node = parent;
+        Some(label)
+    }
+
+    /// Go back until the prefix is `len` labels long. Does nothing if it is
+    /// not longer than that.
+    pub fn truncate(&mut self, len: usize) {
+        let prefix_len = self.prefix_len();
+        if prefix_len > len {
+            self.node = self
+                .trie
+                .child_to_ancestors(self.node)
+                .nth(prefix_len - len)
+                .unwrap_or(LoudsNodeNum(1));
+        }
+    }
+
     /// Reset the query.
     pub fn reset(&mut self) {
         self.node = LoudsNodeNum(1);
     }
 }
 
+/// Iterates over the labels that can follow the prefix of an [IncSearch]. See
+/// [IncSearch::next_labels].
+pub struct NextLabels<'a, Label, Value> {
+    trie: &'a Trie<Label, Value>,
+    children: ChildNodeIter<'a, BitVector>,
+}
+
+impl<'a, Label: Ord, Value> Iterator for NextLabels<'a, Label, Value> {
+    type Item = (&'a Label, Answer);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.children.find_map(|node| {
+            let is_prefix = self.trie.has_children_node_nums(node);
+            let is_match = self.trie.is_terminal(node);
+            Answer::new(is_prefix, is_match).map(|answer| (self.trie.label(node), answer))
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+/// An incremental search that holds on to the trie through an [Arc] rather
+/// than borrowing it, so that it can be kept in `'static` places such as async
+/// tasks, or UI state next to the trie.
+///
+/// It moves like an [IncSearch], and lends one out with
+/// [search()][ArcIncSearch::search] for everything that only looks around.
+///
+/// ```
+/// use std::sync::Arc;
+/// use trie::{inc_search::{Answer, ArcIncSearch}, map::Trie};
+///
+/// let trie = Arc::new(Trie::from_iter(
+///     [("app", 1), ("apple", 2), ("bet", 3)].map(|(k, v)| (k.bytes(), v)),
+/// ));
+/// let mut inc_search = ArcIncSearch::new(Arc::clone(&trie));
+/// let handle = std::thread::spawn(move || {
+///     assert_eq!(inc_search.query_until("app"), Ok(Answer::PrefixAndMatch));
+///     inc_search
+/// });
+/// let mut inc_search = handle.join().unwrap();
+/// assert_eq!(inc_search.search().value(), Some(&1));
+/// assert_eq!(inc_search.pop(), Some(&b'p'));
+/// let completions: Vec<(String, i32)> = inc_search.completions().collect();
+/// assert_eq!(completions, [("app".into(), 1), ("apple".into(), 2)]);
+/// ```
+pub struct ArcIncSearch<Label, Value> {
+    trie: Arc<Trie<Label, Value>>,
+    node: LoudsNodeNum,
+}
+
+impl<Label: Ord, Value> ArcIncSearch<Label, Value> {
+    /// Create a new incremental search for a trie.
+    pub fn new(trie: Arc<Trie<Label, Value>>) -> Self {
+        Self {
+            trie,
+            node: LoudsNodeNum(1),
+        }
+    }
+
+    /// Resume an incremental search at a particular point, if the position
+    /// comes from a trie of the same shape and is at the same key in it. See
+    /// [IncSearch::resume_checked].
+    pub fn resume_checked(
+        trie: Arc<Trie<Label, Value>>,
+        position: Position,
+    ) -> Result<Self, PositionError>
+    where
+        Label: Hash,
+    {
+        let node = IncSearch::resume_checked(&trie, position)?.node;
+        Ok(Self { trie, node })
+    }
+
+    /// Return the trie being searched.
+    pub fn trie(&self) -> &Arc<Trie<Label, Value>> {
+        &self.trie
+    }
+
+    /// Return an [IncSearch] at the current position.
+    pub fn search(&self) -> IncSearch<'_, Label, Value> {
+        IncSearch {
+            trie: &self.trie,
+            node: self.node,
+        }
+    }
+
+    /// Return the position the search is on, to resume it later.
+    pub fn position(&self) -> Position
+    where
+        Label: Hash,
+    {
+        Position::new(&self.trie, self.node)
+    }
+
+    /// Query the trie and go to node if there is a match. See
+    /// [IncSearch::query].
+    pub fn query(&mut self, chr: &Label) -> Option<Answer> {
+        self.with_search(|search| search.query(chr))
+    }
+
+    /// Query the trie with a sequence. See [IncSearch::query_until].
+    pub fn query_until(&mut self, query: impl AsRef<[Label]>) -> Result<Answer, usize> {
+        self.with_search(|search| search.query_until(query))
+    }
+
+    /// Go to the longest key along `query`. See [IncSearch::query_longest].
+    pub fn query_longest(&mut self, query: impl AsRef<[Label]>) -> Option<(usize, &Value)> {
+        self.with_search(|search| search.query_longest(query))
+    }
+
+    /// Go to the longest shared prefix. See [IncSearch::goto_longest_prefix].
+    pub fn goto_longest_prefix(&mut self) -> Result<usize, usize> {
+        self.with_search(|search| search.goto_longest_prefix())
+    }
+
+    /// Go back one label and return it. See [IncSearch::pop].
+    pub fn pop(&mut self) -> Option<&Label> {
+        self.with_search(|search| search.pop())
+    }
+
+    /// Go back until the prefix is `len` labels long. See
+    /// [IncSearch::truncate].
+    pub fn truncate(&mut self, len: usize) {
+        self.with_search(|search| search.truncate(len))
+    }
+
+    /// Reset the query.
+    pub fn reset(&mut self) {
+        self.node = LoudsNodeNum(1);
+    }
+
+    /// Iterate over the entries that start with the current prefix, holding
+    /// on to the trie like the search does. See [IncSearch::completions].
+    pub fn completions<C, M>(&self) -> ArcSearchIter<Label, Value, C, M>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        let key = self.trie.key(self.node);
+        ArcSearchIter::at_node(Arc::clone(&self.trie), self.node, key)
+    }
+
+    /// Move with an [IncSearch] borrowing the trie.
+    fn with_search<'s, R>(
+        &'s mut self,
+        f: impl FnOnce(&mut IncSearch<'s, Label, Value>) -> R,
+    ) -> R {
+        let mut search = IncSearch {
+            trie: &self.trie,
+            node: self.node,
+        };
+        let result = f(&mut search);
+        self.node = search.node;
+        result
+    }
+}
+
 #[cfg(test)]
 mod search_tests {
     use super::*;
@@ -363,6 +723,197 @@ mod search_tests {
         assert_eq!(Some(&2), search.value());
     }
 
+    #[test]
+    fn inc_search_pop_and_truncate() {
+        let trie = build_trie();
+        let mut search = trie.inc_search();
+        assert_eq!(None, search.pop());
+        assert_eq!(Ok(Answer::Prefix), search.query_until("appl"));
+        assert_eq!(Some(&b'l'), search.pop());
+        assert_eq!(Some(&b'p'), search.pop());
+        assert_eq!("ap", search.prefix::<String, _>());
+        assert_eq!(Answer::PrefixAndMatch, search.query(&b'p').unwrap());
+        assert_eq!(Some(&1), search.value());
+
+        assert_eq!(Ok(Answer::Match), search.query_until("lication"));
+        search.truncate(20);
+        assert_eq!(11, search.prefix_len());
+        search.truncate(5);
+        assert_eq!("appli", search.prefix::<String, _>());
+        search.truncate(1);
+        assert_eq!("a", search.prefix::<String, _>());
+        assert_eq!(Some(&0), search.value());
+        search.truncate(0);
+        assert_eq!(0, search.prefix_len());
+        assert_eq!(None, search.pop());
+        assert_eq!(Ok(Answer::Match), search.query_until("better"));
+    }
+
+    #[test]
+    fn inc_search_next_labels() {
+        let trie = build_trie();
+        let mut search = trie.inc_search();
+        let next: Vec<(u8, Answer)> = search.next_labels().map(|(l, a)| (*l, a)).collect();
+        assert_eq!(
+            next,
+            [
+                (b'a', Answer::PrefixAndMatch),
+                (b'b', Answer::Prefix),
+                (0xe3, Answer::Prefix)
+            ]
+        );
+        for (label, answer) in search.next_labels() {
+            assert_eq!(search.peek(label), Some(answer));
+        }
+        search.query_until("appl").unwrap();
+        let next: Vec<(u8, Answer)> = search.next_labels().map(|(l, a)| (*l, a)).collect();
+        assert_eq!(next, [(b'e', Answer::Match), (b'i', Answer::Prefix)]);
+        search.query(&b'e').unwrap();
+        assert_eq!(search.next_labels().count(), 0);
+    }
+
+    #[test]
+    fn inc_search_completions() {
+        let trie = build_trie();
+        let mut search = trie.inc_search();
+        for query in ["", "a", "ap", "appl", "apple", "bet"] {
+            search.reset();
+            search.query_until(query).ok();
+            let completions: Vec<(String, &u8)> = search.completions().collect();
+            let expected: Vec<(String, &u8)> = trie.predictive_search(query).collect();
+            assert_eq!(completions, expected);
+            assert_eq!(search.completions::<String, _>().len(), expected.len());
+        }
+        let mut completions = search.completions::<String, _>().rev();
+        assert_eq!(completions.next(), Some(("better".into(), &3)));
+        assert_eq!(completions.next(), None);
+    }
+
+    #[test]
+    fn inc_search_resume_checked() {
+        let trie = build_trie();
+        let mut search = trie.inc_search();
+        search.query_until("appl").unwrap();
+        let position = search.position();
+        assert_ne!(position, Position::from(search.clone()));
+        assert!(IncSearch::resume_checked(&trie, Position::from(search)).is_ok());
+
+        let mut search = IncSearch::resume_checked(&trie, position).unwrap();
+        assert_eq!("appl", search.prefix::<String, _>());
+        assert_eq!(Answer::Match, search.query(&b'e').unwrap());
+
+        // A trie of the same shape is accepted, other ones are not.
+        let same = trie.clone();
+        assert!(IncSearch::resume_checked(&same, position).is_ok());
+        let mut builder = TrieBuilder::new();
+        builder.insert("apple".bytes(), 0);
+        let other = builder.build();
+        assert_eq!(
+            IncSearch::resume_checked(&other, position).err(),
+            Some(PositionError::OutOfRange)
+        );
+        let mut builder = TrieBuilder::new();
+        for key in ["a", "app", "apple", "better", "applications", "アップル🍎"] {
+            builder.insert(key.bytes(), 0);
+        }
+        let other = builder.build();
+        assert_eq!(
+            IncSearch::resume_checked(&other, position).err(),
+            Some(PositionError::WrongTrie)
+        );
+        let position = IncSearch::resume(&other, position).position();
+        assert!(IncSearch::resume_checked(&other, position).is_ok());
+        assert!(IncSearch::resume_checked(&trie, position).is_err());
+    }
+
+    #[test]
+    fn inc_search_resume_checked_labels() {
+        let build = |keys: &[&str]| {
+            let mut builder = TrieBuilder::new();
+            for key in keys {
+                builder.insert(key.bytes(), 0);
+            }
+            builder.build()
+        };
+        let trie = build(&["ab", "xyz"]);
+        let mut search = trie.inc_search();
+        search.query_until("a").unwrap();
+        let position = search.position();
+
+        // The same shape with other labels.
+        let other = build(&["cd", "xyz"]);
+        assert_eq!(
+            IncSearch::resume_checked(&other, position).err(),
+            Some(PositionError::WrongTrie)
+        );
+        let root = trie.inc_search().position();
+        assert!(IncSearch::resume_checked(&other, root).is_ok());
+
+        // Converted positions carry no key, so only the shape is checked.
+        let mut search = trie.inc_search();
+        search.query_until("a").unwrap();
+        let converted = Position::from(search);
+        assert!(IncSearch::resume_checked(&other, converted).is_ok());
+
+        // Only the labels of the key at the position count.
+        let other = build(&["ab", "xwz"]);
+        let search = IncSearch::resume_checked(&other, position).unwrap();
+        assert_eq!("a", search.prefix::<String, _>());
+    }
+
+    #[test]
+    fn arc_inc_search() {
+        fn assert_send_static<T: Send + 'static>(_: &T) {}
+
+        let trie = Arc::new(build_trie());
+        let mut search = ArcIncSearch::new(Arc::clone(&trie));
+        assert_send_static(&search);
+        assert_eq!(None, search.query(&b'z'));
+        assert_eq!(Ok(Answer::Prefix), search.query_until("appl"));
+        assert_eq!("appl", search.search().prefix::<String, _>());
+        assert_eq!(Some((1, &2)), search.query_longest("e"));
+        assert_eq!(Some(&b'e'), search.pop());
+        search.truncate(2);
+        assert_eq!("ap", search.search().prefix::<String, _>());
+        assert_eq!(Ok(1), search.goto_longest_prefix());
+        assert_eq!(Some(&1), search.search().value());
+
+        let completions = search.completions::<String, _>();
+        assert_send_static(&completions);
+        assert_eq!(completions.len(), 3);
+        let expected: Vec<(String, u8)> = trie
+            .predictive_search("app")
+            .map(|(k, v)| (k, *v))
+            .collect();
+        assert_eq!(completions.collect::<Vec<_>>(), expected);
+
+        let position = search.position();
+        search.reset();
+        assert_eq!(0, search.search().prefix_len());
+        let search = ArcIncSearch::resume_checked(Arc::clone(search.trie()), position).unwrap();
+        assert_eq!("app", search.search().prefix::<String, _>());
+        let other = Arc::new(TrieBuilder::<u8, u8>::new().build());
+        assert!(ArcIncSearch::resume_checked(other, position).is_err());
+    }
+
+    #[test]
+    fn inc_search_query_longest() {
+        let trie = build_trie();
+        let mut search = trie.inc_search();
+        assert_eq!(None, search.query_longest("zoo"));
+        assert_eq!("", search.prefix::<String, _>());
+        assert_eq!(Some((1, &0)), search.query_longest("apt"));
+        assert_eq!("a", search.prefix::<String, _>());
+        assert_eq!(None, search.query_longest("pt"));
+        assert_eq!("a", search.prefix::<String, _>());
+        assert_eq!(Some((10, &4)), search.query_longest("pplications"));
+        assert_eq!("application", search.prefix::<String, _>());
+        assert_eq!(Some(&4), search.value());
+        search.reset();
+        assert_eq!(Some((5, &2)), search.query_longest("apple"));
+        assert_eq!(None, search.query_longest("s"));
+    }
+
     // #[test]
     // fn inc_serach_value_mut() {
     //     let trie = build_trie();
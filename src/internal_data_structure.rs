@@ -1 +1,3 @@
+pub mod fnv;
 pub mod naive_trie;
+pub mod packed_ints;
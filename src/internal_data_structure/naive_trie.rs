@@ -91,4 +91,8 @@ pub struct NaiveTrieIntermOrLeaf<Label, Value> {
     children: Vec<NaiveTrie<Label, Value>>,
     pub(crate) label: Label,
     pub(crate) value: Option<Value>,
+    /// How many keys had been entered when this node's key first was, if it
+    /// ever was.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) order: Option<usize>,
 }
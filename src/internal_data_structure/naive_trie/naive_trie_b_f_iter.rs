@@ -53,8 +53,8 @@ mod bf_iter_tests {
             fn $name() {
                 let (words, expected_nodes) = $value;
                 let mut trie = NaiveTrie::make_root();
-                for word in words {
-                    trie.entry(word.bytes()).replace(());
+                for (order, word) in words.into_iter().enumerate() {
+                    trie.entry(word.bytes(), order).replace(());
                 }
                 let nodes: Vec<NaiveTrie<u8>> = trie.into_iter().collect();
                 assert_eq!(nodes.len(), expected_nodes.len());
@@ -12,12 +12,16 @@ impl<'trie, Label: Ord, Value> NaiveTrie<Label, Value> {
             children: vec![],
             label,
             value: terminal,
+            order: None,
         })
     }
 
+    /// Return the value of `word`, recording `order` as its place in insertion
+    /// order if it is the first time `word` is entered.
     pub fn entry<Arr: Iterator<Item = Label>>(
         &'trie mut self,
         word: Arr,
+        order: usize,
     ) -> &'trie mut Option<Value> {
         let mut trie = self;
         for chr in word {
@@ -50,7 +54,10 @@ impl<'trie, Label: Ord, Value> NaiveTrie<Label, Value> {
             };
         }
         match trie {
-            NaiveTrie::IntermOrLeaf(node) => &mut node.value,
+            NaiveTrie::IntermOrLeaf(node) => {
+                node.order.get_or_insert(order);
+                &mut node.value
+            }
             _ => panic!("Unexpected type"),
         }
     }
@@ -0,0 +1,84 @@
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// A fixed array of unsigned integers, each packed into as many bits as the
+/// largest of them takes.
+///
+/// Numbers that are bounded by the size of a trie, like the number of
+/// entries under each node, take _log n_ bits each rather than a `usize`.
+pub struct PackedInts {
+    words: Vec<u64>,
+    /// The number of bits of each integer, 0 if they are all 0.
+    width: u32,
+    /// The number of integers.
+    len: usize,
+}
+
+impl PackedInts {
+    /// Pack `ints`.
+    pub fn new(ints: &[usize]) -> Self {
+        let max = ints.iter().copied().max().unwrap_or(0) as u64;
+        let width = u64::BITS - max.leading_zeros();
+        let mut words = vec![0; (ints.len() * width as usize).div_ceil(64)];
+        for (i, &int) in ints.iter().enumerate().filter(|&(_, &int)| int != 0) {
+            let bit = i * width as usize;
+            let (word, offset) = (bit / 64, bit % 64);
+            let int = int as u64;
+            words[word] |= int << offset;
+            if offset + width as usize > 64 {
+                words[word + 1] |= int >> (64 - offset);
+            }
+        }
+        Self {
+            words,
+            width,
+            len: ints.len(),
+        }
+    }
+
+    /// Return the `i`th integer.
+    #[inline]
+    pub fn get(&self, i: usize) -> usize {
+        assert!(i < self.len, "index {} out of range of {}", i, self.len);
+        if self.width == 0 {
+            return 0;
+        }
+        let bit = i * self.width as usize;
+        let (word, offset) = (bit / 64, bit % 64);
+        let mut int = self.words[word] >> offset;
+        if offset + self.width as usize > 64 {
+            int |= self.words[word + 1] << (64 - offset);
+        }
+        (int & (u64::MAX >> (64 - self.width))) as usize
+    }
+}
+
+#[cfg(test)]
+mod packed_ints_tests {
+    use super::PackedInts;
+
+    macro_rules! parameterized_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let ints: Vec<usize> = $value;
+                let packed = PackedInts::new(&ints);
+                for (i, &int) in ints.iter().enumerate() {
+                    assert_eq!(packed.get(i), int, "at {}", i);
+                }
+            }
+        )*
+        }
+    }
+
+    parameterized_tests! {
+        t1: vec![],
+        t2: vec![0, 0, 0],
+        t3: vec![1, 0, 1, 1],
+        t4: (0..200).collect(),
+        t5: (0..100).map(|i| (i * 7919) % 1000).collect(),
+        t6: vec![usize::MAX, 0, usize::MAX, 1],
+        t7: (0..70).map(|i| 1 << (i % 40)).collect(),
+    }
+}
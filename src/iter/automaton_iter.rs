@@ -0,0 +1,80 @@
+use crate::automaton::Automaton;
+use crate::map::Trie;
+use crate::try_collect::{TryCollect, TryFromIterator};
+use louds::LoudsNodeNum;
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone)]
+/// Iterates through all the entries matched by an [Automaton].
+pub struct AutomatonIter<'a, Label, Value, A: Automaton<Label>, C, M> {
+    trie: &'a Trie<Label, Value>,
+    aut: A,
+    queue: Vec<(usize, LoudsNodeNum, A::State)>,
+    buffer: Vec<&'a Label>,
+    col: PhantomData<(C, M)>,
+}
+
+impl<'a, Label: Ord + Clone, Value, A: Automaton<Label>, C, M>
+    AutomatonIter<'a, Label, Value, A, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    #[inline]
+    pub(crate) fn new(trie: &'a Trie<Label, Value>, aut: A) -> Self {
+        let start = aut.start();
+        let mut iter = Self {
+            trie,
+            aut,
+            queue: Vec::new(),
+            buffer: Vec::new(),
+            col: PhantomData,
+        };
+        if iter.aut.can_match(&start) {
+            iter.push_children(0, LoudsNodeNum(1), &start);
+        }
+        iter
+    }
+
+    /// Queue the children of `node` from which the automaton can still match.
+    fn push_children(&mut self, depth: usize, node: LoudsNodeNum, state: &A::State) {
+        let start = self.queue.len();
+        for child in self.trie.children_node_nums(node) {
+            let child_state = self.aut.accept(state, self.trie.label(child));
+            if self.aut.can_match(&child_state) {
+                self.queue.push((depth, child, child_state));
+            }
+        }
+        self.queue[start..].reverse();
+    }
+}
+
+impl<'a, Label: Ord + Clone, Value, A: Automaton<Label>, C, M> Iterator
+    for AutomatonIter<'a, Label, Value, A, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    type Item = (C, &'a Value);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((depth, node, state)) = self.queue.pop() {
+            self.buffer.truncate(depth);
+            self.buffer.push(self.trie.label(node));
+            self.push_children(depth + 1, node, &state);
+
+            if self.aut.is_match(&state) {
+                if let Some(v) = self.trie.value(node) {
+                    return Some((
+                        self.buffer
+                            .iter()
+                            .cloned()
+                            .cloned()
+                            .try_collect()
+                            .expect("Could not collect"),
+                        v,
+                    ));
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,103 @@
+use crate::automaton::next_row;
+use crate::map::Trie;
+use crate::try_collect::{TryCollect, TryFromIterator};
+use louds::LoudsNodeNum;
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone)]
+/// Iterates through all the entries within a given edit distance of a query.
+///
+/// Each node carries the Levenshtein row of its prefix against the query, and
+/// subtrees whose row minimum exceeds the allowed edits are never visited.
+///
+/// In predictive mode an entry matches if any of its prefixes is within the
+/// given edit distance, and the reported distance is the smallest one found
+/// along its path.
+pub struct FuzzyIter<'a, Label, Value, C, M> {
+    trie: &'a Trie<Label, Value>,
+    query: Vec<Label>,
+    max_edits: usize,
+    predictive: bool,
+    /// `(depth, node, row, best distance of any prefix on the path)`
+    queue: Vec<(usize, LoudsNodeNum, Vec<usize>, usize)>,
+    buffer: Vec<&'a Label>,
+    col: PhantomData<(C, M)>,
+}
+
+impl<'a, Label: Ord + Clone, Value, C, M> FuzzyIter<'a, Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    #[inline]
+    pub(crate) fn new(
+        trie: &'a Trie<Label, Value>,
+        query: impl AsRef<[Label]>,
+        max_edits: usize,
+        predictive: bool,
+    ) -> Self {
+        let query = query.as_ref().to_vec();
+        let root_row: Vec<usize> = (0..=query.len()).collect();
+        let root_best = query.len();
+        let mut iter = Self {
+            trie,
+            query,
+            max_edits,
+            predictive,
+            queue: Vec::new(),
+            buffer: Vec::new(),
+            col: PhantomData,
+        };
+        iter.push_children(0, LoudsNodeNum(1), &root_row, root_best);
+        iter
+    }
+
+    /// Queue the children of `node` whose rows can still lead to a match.
+    fn push_children(&mut self, depth: usize, node: LoudsNodeNum, row: &[usize], best: usize) {
+        let start = self.queue.len();
+        for child in self.trie.children_node_nums(node) {
+            let child_row = next_row(row, &self.query, self.trie.label(child));
+            let child_best = best.min(child_row[self.query.len()]);
+            let matched = self.predictive && child_best <= self.max_edits;
+            if matched || child_row.iter().any(|&d| d <= self.max_edits) {
+                self.queue.push((depth, child, child_row, child_best));
+            }
+        }
+        self.queue[start..].reverse();
+    }
+}
+
+impl<'a, Label: Ord + Clone, Value, C, M> Iterator for FuzzyIter<'a, Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    type Item = (C, &'a Value, usize);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((depth, node, row, best)) = self.queue.pop() {
+            self.buffer.truncate(depth);
+            self.buffer.push(self.trie.label(node));
+            self.push_children(depth + 1, node, &row, best);
+
+            let distance = if self.predictive {
+                best
+            } else {
+                row[self.query.len()]
+            };
+            if distance <= self.max_edits {
+                if let Some(v) = self.trie.value(node) {
+                    return Some((
+                        self.buffer
+                            .iter()
+                            .cloned()
+                            .cloned()
+                            .try_collect()
+                            .expect("Could not collect"),
+                        v,
+                        distance,
+                    ));
+                }
+            }
+        }
+        None
+    }
+}
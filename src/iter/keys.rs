@@ -20,6 +20,48 @@ where
     fn next(&mut self) -> Option<C> {
         self.0.next().map(|x| x.0)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<I, C, V> DoubleEndedIterator for Keys<I>
+where
+    I: DoubleEndedIterator<Item = (C, V)>,
+{
+    fn next_back(&mut self) -> Option<C> {
+        self.0.next_back().map(|x| x.0)
+    }
+}
+
+impl<I, C, V> ExactSizeIterator for Keys<I> where I: ExactSizeIterator<Item = (C, V)> {}
+
+#[derive(Debug, Clone)]
+/// Retains keys and distances and strips off `Value`s from a [FuzzyIter].
+///
+/// [FuzzyIter]: crate::iter::FuzzyIter
+pub struct FuzzyKeys<I>(I);
+
+impl<I> FuzzyKeys<I> {
+    ///Creates a new `FuzzyKeys` iterator.
+    pub fn new(iter: I) -> Self {
+        Self(iter)
+    }
+}
+
+impl<I, C, V> Iterator for FuzzyKeys<I>
+where
+    I: Iterator<Item = (C, V, usize)>,
+{
+    type Item = (C, usize);
+    fn next(&mut self) -> Option<(C, usize)> {
+        self.0.next().map(|(key, _, distance)| (key, distance))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
 }
 
 /// Strip an iterator items `(K, V)` to only have `K`.
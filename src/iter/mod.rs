@@ -1,10 +1,18 @@
 //! Trie iterators
+mod automaton_iter;
+mod fuzzy_iter;
 mod keys;
+mod pattern_iter;
 mod postfix_iter;
 mod prefix_iter;
+mod range_iter;
 mod search_iter;
 
-pub use keys::{Keys, KeysExt};
-pub use postfix_iter::PostfixIter;
+pub use automaton_iter::AutomatonIter;
+pub use fuzzy_iter::FuzzyIter;
+pub use keys::{FuzzyKeys, Keys, KeysExt};
+pub use pattern_iter::PatternIter;
+pub use postfix_iter::{ArcPostfixIter, PostfixIter};
 pub use prefix_iter::PrefixIter;
-pub use search_iter::SearchIter;
+pub use range_iter::RangeIter;
+pub use search_iter::{ArcSearchIter, SearchIter};
@@ -0,0 +1,153 @@
+use crate::map::Trie;
+use crate::pattern::PatternElem;
+use crate::try_collect::{TryCollect, TryFromIterator};
+use louds::LoudsNodeNum;
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone)]
+/// Iterates through all the entries matching a pattern.
+///
+/// Each node carries the set of pattern positions its prefix can have reached,
+/// so only children whose labels satisfy one of those positions are visited.
+pub struct PatternIter<'a, Label, Value, C, M> {
+    trie: &'a Trie<Label, Value>,
+    pattern: Vec<PatternElem<Label>>,
+    queue: Vec<(usize, LoudsNodeNum, Vec<usize>)>,
+    buffer: Vec<&'a Label>,
+    col: PhantomData<(C, M)>,
+}
+
+impl<'a, Label: Ord + Clone, Value, C, M> PatternIter<'a, Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    #[inline]
+    pub(crate) fn new(
+        trie: &'a Trie<Label, Value>,
+        pattern: impl AsRef<[PatternElem<Label>]>,
+    ) -> Self {
+        let pattern: Vec<_> = pattern
+            .as_ref()
+            .iter()
+            .cloned()
+            .map(|elem| match elem {
+                PatternElem::OneOf(mut set) => {
+                    set.sort();
+                    set.dedup();
+                    PatternElem::OneOf(set)
+                }
+                elem => elem,
+            })
+            .collect();
+        let root_states = closure(&pattern, vec![0]);
+        let mut iter = Self {
+            trie,
+            pattern,
+            queue: Vec::new(),
+            buffer: Vec::new(),
+            col: PhantomData,
+        };
+        iter.push_children(0, LoudsNodeNum(1), &root_states);
+        iter
+    }
+
+    /// Queue the children of `node` that some pattern position in `states`
+    /// accepts.
+    fn push_children(&mut self, depth: usize, node: LoudsNodeNum, states: &[usize]) {
+        let children = self.trie.children_range(node);
+        let wildcard = states.iter().any(|&i| {
+            matches!(
+                self.pattern.get(i),
+                Some(PatternElem::Any | PatternElem::AnySeq)
+            )
+        });
+
+        let mut candidates: Vec<u64> = if wildcard {
+            children.collect()
+        } else {
+            let mut candidates = Vec::new();
+            for &i in states {
+                let labels = match self.pattern.get(i) {
+                    Some(PatternElem::Exact(label)) => std::slice::from_ref(label),
+                    Some(PatternElem::OneOf(set)) => &set[..],
+                    _ => continue,
+                };
+                for label in labels {
+                    let res = self
+                        .trie
+                        .bin_search_by_children_labels(label, children.clone());
+                    if let Ok(child) = res {
+                        candidates.push(child);
+                    }
+                }
+            }
+            candidates.sort_unstable();
+            candidates.dedup();
+            candidates
+        };
+
+        // Children are queued in reverse so they pop off in label order.
+        candidates.reverse();
+        for child in candidates {
+            let child = LoudsNodeNum(child);
+            let label = self.trie.label(child);
+            let next: Vec<usize> = states
+                .iter()
+                .filter_map(|&i| match self.pattern.get(i) {
+                    Some(PatternElem::AnySeq) => Some(i),
+                    Some(elem) if elem.accepts(label) => Some(i + 1),
+                    _ => None,
+                })
+                .collect();
+            if !next.is_empty() {
+                self.queue
+                    .push((depth, child, closure(&self.pattern, next)));
+            }
+        }
+    }
+}
+
+/// Add the positions reachable by letting `AnySeq` elements match nothing.
+/// Returns the positions sorted and without duplicates.
+fn closure<Label>(pattern: &[PatternElem<Label>], states: Vec<usize>) -> Vec<usize> {
+    let mut reached = vec![false; pattern.len() + 1];
+    for i in states {
+        reached[i] = true;
+    }
+    for i in 0..pattern.len() {
+        if reached[i] && matches!(pattern[i], PatternElem::AnySeq) {
+            reached[i + 1] = true;
+        }
+    }
+    (0..=pattern.len()).filter(|&i| reached[i]).collect()
+}
+
+impl<'a, Label: Ord + Clone, Value, C, M> Iterator for PatternIter<'a, Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    type Item = (C, &'a Value);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((depth, node, states)) = self.queue.pop() {
+            self.buffer.truncate(depth);
+            self.buffer.push(self.trie.label(node));
+            self.push_children(depth + 1, node, &states);
+
+            if states.last() == Some(&self.pattern.len()) {
+                if let Some(v) = self.trie.value(node) {
+                    return Some((
+                        self.buffer
+                            .iter()
+                            .cloned()
+                            .cloned()
+                            .try_collect()
+                            .expect("Could not collect"),
+                        v,
+                    ));
+                }
+            }
+        }
+        None
+    }
+}
@@ -2,14 +2,143 @@ use crate::map::Trie;
 use crate::try_collect::{TryCollect, TryFromIterator};
 use louds::LoudsNodeNum;
 use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+/// Walks the subtree of a node in lexicographical order from both ends, for
+/// [PostfixIter] and [ArcPostfixIter], whichever way they hold the trie.
+pub(crate) struct Postfixes<T> {
+    pub(crate) trie: T,
+    queue: Vec<(usize, LoudsNodeNum)>,
+    /// The nodes leading to the node last visited from the front.
+    buffer: Vec<LoudsNodeNum>,
+    /// `(depth, node, children already queued)`
+    back_queue: Vec<(usize, LoudsNodeNum, bool)>,
+    /// The nodes leading to the node last visited from the back.
+    back_buffer: Vec<LoudsNodeNum>,
+    /// Has either end yielded its first postfix, which is then in its buffer?
+    started: (bool, bool),
+}
+
+impl<T, Label: Ord, Value> Postfixes<T>
+where
+    T: Deref<Target = Trie<Label, Value>>,
+{
+    pub(crate) fn new(trie: T, root: LoudsNodeNum) -> Self {
+        let mut children: Vec<_> = trie.children_node_nums(root).map(|n| (0, n)).collect();
+        let back_children = children.iter().map(|&(d, n)| (d, n, false)).collect();
+        children.reverse();
+        Self {
+            trie,
+            queue: children,
+            buffer: Vec::new(),
+            back_queue: back_children,
+            back_buffer: Vec::new(),
+            started: (false, false),
+        }
+    }
+
+    pub(crate) fn empty(trie: T) -> Self {
+        Self {
+            trie,
+            queue: Vec::new(),
+            buffer: Vec::new(),
+            back_queue: Vec::new(),
+            back_buffer: Vec::new(),
+            started: (false, false),
+        }
+    }
+
+    /// Stop both ends.
+    fn finish(&mut self) {
+        self.queue.clear();
+        self.back_queue.clear();
+    }
+
+    /// Have the two ends met? Siblings are numbered in label order, so the
+    /// node numbers leading to two nodes compare like their postfixes.
+    fn crossed(&self) -> bool {
+        let front = self.buffer.iter().map(|node| node.0);
+        front.ge(self.back_buffer.iter().map(|node| node.0))
+    }
+
+    /// Go to the next terminal from the front, with its postfix in
+    /// [postfix()][Postfixes::postfix].
+    pub(crate) fn next(&mut self) -> Option<LoudsNodeNum> {
+        while let Some((depth, node)) = self.queue.pop() {
+            let children = self.trie.children_node_nums(node);
+            self.queue
+                .extend(children.rev().map(|child| (depth + 1, child)));
+            self.buffer.truncate(depth);
+            self.buffer.push(node);
+
+            if self.trie.is_terminal(node) {
+                if self.started.1 && self.crossed() {
+                    self.finish();
+                    return None;
+                }
+                self.started.0 = true;
+                return Some(node);
+            }
+        }
+        None
+    }
+
+    /// Go to the next terminal from the back, with its postfix in
+    /// [back_postfix()][Postfixes::back_postfix].
+    pub(crate) fn next_back(&mut self) -> Option<LoudsNodeNum> {
+        // A node comes after all of its descendants in descending order.
+        while let Some((depth, node, expanded)) = self.back_queue.pop() {
+            if !expanded {
+                self.back_buffer.truncate(depth);
+                self.back_buffer.push(node);
+                self.back_queue.push((depth, node, true));
+                let children = self.trie.children_node_nums(node);
+                self.back_queue
+                    .extend(children.map(|child| (depth + 1, child, false)));
+                continue;
+            }
+
+            self.back_buffer.truncate(depth + 1);
+            if self.trie.is_terminal(node) {
+                if self.started.0 && self.crossed() {
+                    self.finish();
+                    return None;
+                }
+                self.started.1 = true;
+                return Some(node);
+            }
+        }
+        None
+    }
+
+    /// Return the labels leading to the node last visited from the front.
+    pub(crate) fn postfix<'s>(&'s self) -> impl Iterator<Item = &'s Label>
+    where
+        Label: 's,
+        Value: 's,
+    {
+        self.buffer.iter().map(|&node| self.trie.label(node))
+    }
+
+    /// Return the labels leading to the node last visited from the back.
+    pub(crate) fn back_postfix<'s>(&'s self) -> impl Iterator<Item = &'s Label>
+    where
+        Label: 's,
+        Value: 's,
+    {
+        self.back_buffer.iter().map(|&node| self.trie.label(node))
+    }
+}
 
 #[derive(Debug, Clone)]
 /// Iterates through all the postfixes of a matching query.
+///
+/// Iterating from the back yields the postfixes in descending lexicographical
+/// order. The two ends stop once they meet.
 pub struct PostfixIter<'a, Label, Value, C, M> {
-    trie: &'a Trie<Label, Value>,
-    queue: Vec<(usize, LoudsNodeNum)>,
-    buffer: Vec<&'a Label>,
-    value: Option<&'a Value>,
+    postfixes: Postfixes<&'a Trie<Label, Value>>,
     col: PhantomData<(C, M)>,
 }
 
@@ -19,13 +148,8 @@ where
 {
     #[inline]
     pub(crate) fn new(trie: &'a Trie<Label, Value>, root: LoudsNodeNum) -> Self {
-        let mut children: Vec<_> = trie.children_node_nums(root).map(|n| (0, n)).collect();
-        children.reverse();
         Self {
-            trie,
-            queue: children,
-            buffer: Vec::new(),
-            value: None,
+            postfixes: Postfixes::new(trie, root),
             col: PhantomData,
         }
     }
@@ -33,10 +157,7 @@ where
     #[inline]
     pub(crate) fn empty(trie: &'a Trie<Label, Value>) -> Self {
         Self {
-            trie,
-            queue: Vec::new(),
-            buffer: Vec::new(),
-            value: None,
+            postfixes: Postfixes::empty(trie),
             col: PhantomData,
         }
     }
@@ -49,45 +170,103 @@ where
     type Item = (C, &'a Value);
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        use std::cmp::Ordering;
-        while self.value.is_none() {
-            if let Some((depth, node)) = self.queue.pop() {
-                let children = self.trie.children_node_nums(node);
-                self.queue
-                    .extend(children.rev().map(|child| (depth + 1, child)));
-                match depth.cmp(&self.buffer.len()) {
-                    Ordering::Equal => {
-                        self.buffer.push(self.trie.label(node));
-                    }
-                    Ordering::Less => {
-                        let _ = self.buffer.drain(depth + 1..);
-                        self.buffer[depth] = self.trie.label(node);
-                    }
-                    Ordering::Greater => {
-                        panic!("depth > buffer.len()");
-                    }
-                }
-                self.value = self.trie.value(node);
-            } else {
-                break;
-            }
-        }
-        if let Some(v) = self.value.take() {
-            Some((
-                self.buffer
-                    .iter()
-                    .cloned()
-                    .cloned()
-                    .try_collect()
-                    .expect("Could not collect"),
-                v,
-            ))
-        } else {
-            None
+        let node = self.postfixes.next()?;
+        let trie: &'a Trie<Label, Value> = self.postfixes.trie;
+        let postfix = self
+            .postfixes
+            .postfix()
+            .cloned()
+            .try_collect()
+            .expect("Could not collect");
+        Some((postfix, trie.value(node).expect("Not a terminal")))
+    }
+}
+
+impl<'a, Label: Ord + Clone, Value, C, M> DoubleEndedIterator
+    for PostfixIter<'a, Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.postfixes.next_back()?;
+        let trie: &'a Trie<Label, Value> = self.postfixes.trie;
+        let postfix = self
+            .postfixes
+            .back_postfix()
+            .cloned()
+            .try_collect()
+            .expect("Could not collect");
+        Some((postfix, trie.value(node).expect("Not a terminal")))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Iterates through all the postfixes of a matching query, like
+/// [PostfixIter], but holding on to the trie through an [Arc] rather than
+/// borrowing it.
+///
+/// Since nothing can borrow from the iterator, it yields clones of the values.
+/// See [Trie::into_postfix_search].
+pub struct ArcPostfixIter<Label, Value, C, M> {
+    postfixes: Postfixes<Arc<Trie<Label, Value>>>,
+    col: PhantomData<(C, M)>,
+}
+
+impl<Label: Ord, Value, C, M> ArcPostfixIter<Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    pub(crate) fn new(trie: Arc<Trie<Label, Value>>, root: Option<LoudsNodeNum>) -> Self {
+        let postfixes = match root {
+            Some(root) => Postfixes::new(trie, root),
+            None => Postfixes::empty(trie),
+        };
+        Self {
+            postfixes,
+            col: PhantomData,
         }
     }
 }
 
+impl<Label: Ord + Clone, Value: Clone, C, M> Iterator for ArcPostfixIter<Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    type Item = (C, Value);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.postfixes.next()?;
+        let postfix = self
+            .postfixes
+            .postfix()
+            .cloned()
+            .try_collect()
+            .expect("Could not collect");
+        let value = self.postfixes.trie.value(node).expect("Not a terminal");
+        Some((postfix, value.clone()))
+    }
+}
+
+impl<Label: Ord + Clone, Value: Clone, C, M> DoubleEndedIterator
+    for ArcPostfixIter<Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.postfixes.next_back()?;
+        let postfix = self
+            .postfixes
+            .back_postfix()
+            .cloned()
+            .try_collect()
+            .expect("Could not collect");
+        let value = self.postfixes.trie.value(node).expect("Not a terminal");
+        Some((postfix, value.clone()))
+    }
+}
+
 // impl<Label: Ord, V, C, M> Value<V> for PostfixIter<'_, Label, V, C, M> {
 //     fn value(&self) -> Option<&V> {
 //         self.value
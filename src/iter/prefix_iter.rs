@@ -1,21 +1,29 @@
 use crate::map::Trie;
 use crate::try_collect::{TryCollect, TryFromIterator};
 use louds::LoudsNodeNum;
+use std::collections::VecDeque;
 use std::marker::PhantomData;
 
 #[derive(Debug, Clone)]
 /// Iterates through all the common prefixes of a given query.
+///
+/// Iterating from the back walks the rest of the query first, then yields the
+/// longest prefix first.
 pub struct PrefixIter<'a, Label, Value, C, M> {
     trie: &'a Trie<Label, Value>,
     query: Vec<Label>,
     index: usize,
     node: LoudsNodeNum,
     buffer: Vec<&'a Label>,
-    consume: Option<&'a Value>,
+    /// Prefixes walked but not yielded yet, as `(length, value)`.
+    pending: VecDeque<(usize, &'a Value)>,
     col: PhantomData<(C, M)>,
 }
 
-impl<'a, Label: Ord + Clone, Value, C, M> PrefixIter<'a, Label, Value, C, M> {
+impl<'a, Label: Ord + Clone, Value, C, M> PrefixIter<'a, Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
     #[inline]
     pub(crate) fn new(trie: &'a Trie<Label, Value>, query: impl AsRef<[Label]>) -> Self {
         Self {
@@ -24,10 +32,46 @@ impl<'a, Label: Ord + Clone, Value, C, M> PrefixIter<'a, Label, Value, C, M> {
             index: 0,
             node: LoudsNodeNum(1),
             buffer: Vec::new(),
-            consume: None,
+            pending: VecDeque::new(),
             col: PhantomData,
         }
     }
+
+    /// Walk down the query, queuing the prefixes that have a value. Stops at
+    /// the first one if `stop_at_match`, and at the end of the query otherwise.
+    fn walk(&mut self, stop_at_match: bool) {
+        while let Some(chr) = self.query.get(self.index) {
+            match self.trie.child(self.node, chr) {
+                Some(child_node_num) => {
+                    self.buffer.push(self.trie.label(child_node_num));
+                    self.node = child_node_num;
+                    self.index += 1;
+                    if let Some(v) = self.trie.value(child_node_num) {
+                        self.pending.push_back((self.buffer.len(), v));
+                        if stop_at_match {
+                            return;
+                        }
+                    }
+                }
+                None => {
+                    self.index = self.query.len();
+                    return;
+                }
+            }
+        }
+    }
+
+    fn entry(&self, (len, v): (usize, &'a Value)) -> (C, &'a Value) {
+        (
+            self.buffer[..len]
+                .iter()
+                .cloned()
+                .cloned()
+                .try_collect()
+                .expect("Could not collect"),
+            v,
+        )
+    }
 }
 
 impl<'a, Label: Ord + Clone, Value, C, M> Iterator for PrefixIter<'a, Label, Value, C, M>
@@ -36,37 +80,19 @@ where
 {
     type Item = (C, &'a Value);
     fn next(&mut self) -> Option<Self::Item> {
-        while self.consume.is_none() {
-            if let Some(chr) = self.query.get(self.index) {
-                let children_node_nums: Vec<_> = self.trie.children_node_nums(self.node).collect();
-                let res = self
-                    .trie
-                    .bin_search_by_children_labels(chr, &children_node_nums[..]);
-                match res {
-                    Ok(j) => {
-                        let child_node_num = children_node_nums[j];
-                        self.buffer.push(self.trie.label(child_node_num));
-                        self.consume = self.trie.value(child_node_num);
-                        self.node = child_node_num;
-                    }
-                    Err(_) => break,
-                }
-            } else {
-                return None;
-            }
-            self.index += 1;
-        }
-        if let Some(v) = self.consume.take() {
-            let col = self.buffer.clone();
-            Some((
-                col.into_iter()
-                    .cloned()
-                    .try_collect()
-                    .expect("Could not collect"),
-                v,
-            ))
-        } else {
-            None
+        if self.pending.is_empty() {
+            self.walk(true);
         }
+        self.pending.pop_front().map(|x| self.entry(x))
+    }
+}
+
+impl<'a, Label: Ord + Clone, Value, C, M> DoubleEndedIterator for PrefixIter<'a, Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.walk(false);
+        self.pending.pop_back().map(|x| self.entry(x))
     }
 }
@@ -0,0 +1,134 @@
+use crate::map::Trie;
+use crate::try_collect::{TryCollect, TryFromIterator};
+use louds::LoudsNodeNum;
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+#[derive(Debug, Clone)]
+/// Iterates through all the entries within a range of keys, in order.
+///
+/// Starts directly at the lower bound, and stops at the first node past the
+/// upper bound.
+pub struct RangeIter<'a, Label, Value, C, M> {
+    trie: &'a Trie<Label, Value>,
+    end: Bound<Vec<Label>>,
+    queue: Vec<(usize, LoudsNodeNum)>,
+    buffer: Vec<&'a Label>,
+    col: PhantomData<(C, M)>,
+}
+
+impl<'a, Label: Ord + Clone, Value, C, M> RangeIter<'a, Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    pub(crate) fn new<K: AsRef<[Label]> + ?Sized>(
+        trie: &'a Trie<Label, Value>,
+        range: impl RangeBounds<K>,
+    ) -> Self {
+        let end = match range.end_bound() {
+            Bound::Included(k) => Bound::Included(k.as_ref().to_vec()),
+            Bound::Excluded(k) => Bound::Excluded(k.as_ref().to_vec()),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let mut iter = Self {
+            trie,
+            end,
+            queue: Vec::new(),
+            buffer: Vec::new(),
+            col: PhantomData,
+        };
+        let (start, inclusive) = match range.start_bound() {
+            Bound::Included(k) => (k.as_ref(), true),
+            Bound::Excluded(k) => (k.as_ref(), false),
+            Bound::Unbounded => (&[][..], false),
+        };
+        iter.seek(start, inclusive);
+        iter
+    }
+
+    /// Queue the nodes that come at or after `start` in order. Every subtree
+    /// branching off `start` with a greater label is queued whole, from the
+    /// shallowest to the deepest.
+    fn seek(&mut self, start: &[Label], inclusive: bool) {
+        let mut cur_node_num = LoudsNodeNum(1);
+        for (depth, chr) in start.iter().enumerate() {
+            let children = self.trie.children_range(cur_node_num);
+            let res = self
+                .trie
+                .bin_search_by_children_labels(chr, children.clone());
+            let greater = match res {
+                Ok(child) => child + 1,
+                Err(child) => child,
+            };
+            self.queue.extend(
+                (greater..children.end)
+                    .rev()
+                    .map(|child| (depth, LoudsNodeNum(child))),
+            );
+            match res {
+                Ok(child) => {
+                    cur_node_num = LoudsNodeNum(child);
+                    self.buffer.push(self.trie.label(cur_node_num));
+                }
+                Err(_) => return,
+            }
+        }
+
+        if inclusive && !start.is_empty() {
+            self.queue.push((start.len() - 1, cur_node_num));
+        } else {
+            let children = self.trie.children_node_nums(cur_node_num);
+            self.queue
+                .extend(children.rev().map(|child| (start.len(), child)));
+        }
+    }
+}
+
+impl<'a, Label: Ord + Clone, Value, C, M> Iterator for RangeIter<'a, Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    type Item = (C, &'a Value);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((depth, node)) = self.queue.pop() {
+            self.buffer.truncate(depth);
+            self.buffer.push(self.trie.label(node));
+
+            // Everything after a node past the end is past the end too, and so
+            // is everything after the end itself.
+            let ord = match &self.end {
+                Bound::Included(end) | Bound::Excluded(end) => {
+                    self.buffer.iter().copied().cmp(end.iter())
+                }
+                Bound::Unbounded => Ordering::Less,
+            };
+            match (ord, &self.end) {
+                (Ordering::Less, _) => {
+                    let children = self.trie.children_node_nums(node);
+                    self.queue
+                        .extend(children.rev().map(|child| (depth + 1, child)));
+                }
+                (Ordering::Equal, Bound::Included(_)) => self.queue.clear(),
+                _ => {
+                    self.queue.clear();
+                    return None;
+                }
+            }
+
+            if let Some(v) = self.trie.value(node) {
+                return Some((
+                    self.buffer
+                        .iter()
+                        .cloned()
+                        .cloned()
+                        .try_collect()
+                        .expect("Could not collect"),
+                    v,
+                ));
+            }
+        }
+        None
+    }
+}
@@ -1,15 +1,121 @@
-use crate::iter::PostfixIter;
+use crate::iter::postfix_iter::Postfixes;
 use crate::map::Trie;
-use crate::try_collect::{Collect, TryCollect, TryFromIterator};
+use crate::try_collect::{Collect, TryFromIterator};
 use louds::LoudsNodeNum;
+use std::cell::OnceCell;
 use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+/// The matches of a query in lexicographical order from both ends, for
+/// [SearchIter] and [ArcSearchIter], whichever way they hold the trie.
+pub(crate) struct Matches<T, Label> {
+    /// The labels of the query.
+    prefix: Vec<Label>,
+    /// The node the query leads to, if it is a terminal not yielded yet.
+    first: Option<LoudsNodeNum>,
+    postfixes: Postfixes<T>,
+    /// The node the query leads to, if any.
+    root: Option<LoudsNodeNum>,
+    /// The number of matches under `root`, counted on the first call to
+    /// [len()][Matches::len] rather than up front, since without stored counts
+    /// that takes a walk of the whole subtree.
+    count: OnceCell<usize>,
+    yielded: usize,
+}
+
+impl<T, Label: Ord + Clone, Value> Matches<T, Label>
+where
+    T: Deref<Target = Trie<Label, Value>>,
+{
+    fn new(trie: T, query: impl AsRef<[Label]>) -> Self {
+        let query = query.as_ref();
+        match trie.prefix_node(query) {
+            Some(node) => Self::at_node(trie, node, query.to_vec()),
+            None => Self {
+                prefix: Vec::new(),
+                first: None,
+                postfixes: Postfixes::empty(trie),
+                root: None,
+                count: OnceCell::new(),
+                yielded: 0,
+            },
+        }
+    }
+
+    /// The matches of `prefix`, which leads to `node`.
+    fn at_node(trie: T, node: LoudsNodeNum, prefix: Vec<Label>) -> Self {
+        Self {
+            prefix,
+            first: trie.is_terminal(node).then_some(node),
+            postfixes: Postfixes::new(trie, node),
+            root: Some(node),
+            count: OnceCell::new(),
+            yielded: 0,
+        }
+    }
+
+    /// Return the number of matches left.
+    fn len(&self) -> usize {
+        let count = self
+            .count
+            .get_or_init(|| self.root.map_or(0, |root| self.trie().subtree_count(root)));
+        count - self.yielded
+    }
+
+    fn trie(&self) -> &T {
+        &self.postfixes.trie
+    }
+
+    /// Return the next match from the front, and its node.
+    fn next<C, M>(&mut self) -> Option<(C, LoudsNodeNum)>
+    where
+        C: TryFromIterator<Label, M>,
+    {
+        let (key, node) = match self.first.take() {
+            Some(node) => (C::try_from_iter(self.prefix.iter().cloned()), node),
+            None => {
+                let node = self.postfixes.next()?;
+                let postfix = self.postfixes.postfix().cloned();
+                let key = self.prefix.iter().cloned().chain(postfix);
+                (C::try_from_iter(key), node)
+            }
+        };
+        self.yielded += 1;
+        Some((key.expect("Could not collect"), node))
+    }
+
+    /// Return the next match from the back, and its node.
+    fn next_back<C, M>(&mut self) -> Option<(C, LoudsNodeNum)>
+    where
+        C: TryFromIterator<Label, M>,
+    {
+        // The query itself is the smallest match, so it comes out last.
+        let (key, node) = match self.postfixes.next_back() {
+            Some(node) => {
+                let postfix = self.postfixes.back_postfix().cloned();
+                let key = self.prefix.iter().cloned().chain(postfix);
+                (C::try_from_iter(key), node)
+            }
+            None => {
+                let node = self.first.take()?;
+                (C::try_from_iter(self.prefix.iter().cloned()), node)
+            }
+        };
+        self.yielded += 1;
+        Some((key.expect("Could not collect"), node))
+    }
+}
 
 #[derive(Debug, Clone)]
 /// Iterates through all the matches of a query.
+///
+/// Knows the exact number of matches left, see [Trie::count_prefix]. They are
+/// counted on the first call to `len()` or `size_hint()`, so iterating through
+/// the first few matches does not pay for counting all of them.
 pub struct SearchIter<'a, Label, Value, C, M> {
-    prefix: Vec<Label>,
-    first: Option<(C, &'a Value)>,
-    postfix_iter: PostfixIter<'a, Label, Value, Vec<Label>, Collect>,
+    matches: Matches<&'a Trie<Label, Value>, Label>,
     col: PhantomData<(C, M)>,
 }
 
@@ -18,43 +124,20 @@ where
     C: TryFromIterator<Label, M> + Clone,
 {
     pub(crate) fn new(trie: &'a Trie<Label, Value>, query: impl AsRef<[Label]>) -> Self {
-        let mut cur_node_num = LoudsNodeNum(1);
-        let mut prefix = Vec::new();
-
-        // Consumes query (prefix)
-        for chr in query.as_ref() {
-            let children_node_nums: Vec<_> = trie.children_node_nums(cur_node_num).collect();
-            let res = trie.bin_search_by_children_labels(chr, &children_node_nums[..]);
-            match res {
-                Ok(i) => cur_node_num = children_node_nums[i],
-                Err(_) => return Self::empty(trie),
-            }
-            prefix.push(trie.label(cur_node_num).clone());
-        }
-        // let prefix:  = prefix.into_iter().try_collect().expect("Could not collect");
-        let first = trie.value(cur_node_num).map(|v| {
-            (
-                prefix
-                    .clone()
-                    .into_iter()
-                    .try_collect()
-                    .expect("Could not collect"),
-                v,
-            )
-        });
-        SearchIter {
-            prefix,
-            first,
-            postfix_iter: PostfixIter::new(trie, cur_node_num),
+        Self {
+            matches: Matches::new(trie, query),
             col: PhantomData,
         }
     }
 
-    fn empty(trie: &'a Trie<Label, Value>) -> Self {
-        SearchIter {
-            prefix: Vec::new(),
-            first: None,
-            postfix_iter: PostfixIter::empty(trie),
+    /// Iterate through the matches of `prefix`, which leads to `node`.
+    pub(crate) fn at_node(
+        trie: &'a Trie<Label, Value>,
+        node: LoudsNodeNum,
+        prefix: Vec<Label>,
+    ) -> Self {
+        Self {
+            matches: Matches::at_node(trie, node, prefix),
             col: PhantomData,
         }
     }
@@ -68,19 +151,117 @@ where
     type Item = (C, &'a Value);
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        match self.first.take() {
-            // None => None,
-            None => self.postfix_iter.next().map(|(postfix, v)| {
-                let entry = C::try_from_iter(self.prefix.clone().into_iter().chain(postfix))
-                    .expect("Could not collect postfix");
-                // let mut entry = self.prefix.clone();
-                // let ext: C = postfix.into_iter().try_collect().expect("Could not collect postfix");
-                // entry.extend([ext]);
-                (entry, v)
-            }),
-            x => x,
+        let (key, node) = self.matches.next()?;
+        let trie: &'a Trie<Label, Value> = self.matches.trie();
+        Some((key, trie.value(node).expect("Not a terminal")))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, Label: Ord + Clone, Value, C, M> DoubleEndedIterator for SearchIter<'a, Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M> + Clone,
+    Vec<Label>: TryFromIterator<Label, Collect>,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (key, node) = self.matches.next_back()?;
+        let trie: &'a Trie<Label, Value> = self.matches.trie();
+        Some((key, trie.value(node).expect("Not a terminal")))
+    }
+}
+
+impl<'a, Label: Ord + Clone, Value, C, M> ExactSizeIterator for SearchIter<'a, Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M> + Clone,
+    Vec<Label>: TryFromIterator<Label, Collect>,
+{
+    #[inline]
+    fn len(&self) -> usize {
+        self.matches.len()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Iterates through all the matches of a query, like [SearchIter], but
+/// holding on to the trie through an [Arc] rather than borrowing it.
+///
+/// Since nothing can borrow from the iterator, it yields clones of the values.
+/// See [Trie::into_predictive_search].
+pub struct ArcSearchIter<Label, Value, C, M> {
+    matches: Matches<Arc<Trie<Label, Value>>, Label>,
+    col: PhantomData<(C, M)>,
+}
+
+impl<Label: Ord + Clone, Value, C, M> ArcSearchIter<Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    pub(crate) fn new(trie: Arc<Trie<Label, Value>>, query: impl AsRef<[Label]>) -> Self {
+        Self {
+            matches: Matches::new(trie, query),
+            col: PhantomData,
         }
     }
+
+    /// Iterate through the matches of `prefix`, which leads to `node`.
+    pub(crate) fn at_node(
+        trie: Arc<Trie<Label, Value>>,
+        node: LoudsNodeNum,
+        prefix: Vec<Label>,
+    ) -> Self {
+        Self {
+            matches: Matches::at_node(trie, node, prefix),
+            col: PhantomData,
+        }
+    }
+}
+
+impl<Label: Ord + Clone, Value: Clone, C, M> Iterator for ArcSearchIter<Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    type Item = (C, Value);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, node) = self.matches.next()?;
+        let value = self.matches.trie().value(node).expect("Not a terminal");
+        Some((key, value.clone()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<Label: Ord + Clone, Value: Clone, C, M> DoubleEndedIterator
+    for ArcSearchIter<Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (key, node) = self.matches.next_back()?;
+        let value = self.matches.trie().value(node).expect("Not a terminal");
+        Some((key, value.clone()))
+    }
+}
+
+impl<Label: Ord + Clone, Value: Clone, C, M> ExactSizeIterator for ArcSearchIter<Label, Value, C, M>
+where
+    C: TryFromIterator<Label, M>,
+{
+    #[inline]
+    fn len(&self) -> usize {
+        self.matches.len()
+    }
 }
 
 // impl<'a, Label: Ord + Clone, Value, C> Iterator for SearchIter<'a, Label, Value, C, Collect>
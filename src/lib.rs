@@ -2,10 +2,16 @@
 //#![doc(html_root_url = "https://docs.rs/trie-rs/0.4.2")]
 #![doc = include_str!("../README.md")]
 
+pub mod aho_corasick;
+pub mod automaton;
 pub mod inc_search;
 mod internal_data_structure;
 pub mod iter;
 pub mod map;
+pub mod node_ref;
+pub mod pattern;
+pub mod segment;
+pub mod tokenizer;
 mod trie;
 pub mod try_collect;
 
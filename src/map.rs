@@ -1,9 +1,14 @@
 //! A trie that maps sequence of `Label`s to a `Value`.
 
 use crate::internal_data_structure::naive_trie::NaiveTrie;
+use crate::internal_data_structure::packed_ints::PackedInts;
 use fid::BitVector;
 use louds::Louds;
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::OnceLock;
 
+mod combine;
 mod trie;
 mod trie_builder;
 
@@ -19,6 +24,28 @@ pub struct Trie<Label, Value> {
     terminals: BitVector,
 
     values: Vec<Value>,
+
+    /// The number of terminals in each node's subtree, itself included,
+    /// indexed by node number, in as many bits each as the number of entries
+    /// takes. Only kept if built with [BuildOptions::counts].
+    #[cfg_attr(feature = "serde", serde(default))]
+    subtree_counts: Option<PackedInts>,
+
+    /// Where the entries rank by score. Only kept if built with
+    /// [BuildOptions::scores].
+    #[cfg_attr(feature = "serde", serde(default))]
+    score_ranks: Option<Ranks>,
+
+    /// Where the entries rank in insertion order. Only kept if built with
+    /// [BuildOptions::insertion_order].
+    #[cfg_attr(feature = "serde", serde(default))]
+    insertion_ranks: Option<Ranks>,
+
+    /// A hash of the shape of the trie, to check that an
+    /// [crate::inc_search::Position] comes from it. It is worked out the first
+    /// time a position is taken or checked, and not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    fingerprint: OnceLock<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -27,4 +54,52 @@ pub struct Trie<Label, Value> {
 /// A trie builder for [Trie].
 pub struct TrieBuilder<Label, Value> {
     naive_trie: NaiveTrie<Label, Value>,
+    /// The number of keys entered so far, to number them in insertion order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    entered: usize,
+}
+
+/// What a [TrieBuilder] stores in a [Trie] besides its entries, to speed up
+/// some queries. See [TrieBuilder::build_with].
+///
+/// ```rust
+/// use trie::map::{BuildOptions, TrieBuilder};
+///
+/// let mut builder = TrieBuilder::new();
+/// builder.insert("apple".bytes(), 30);
+/// builder.insert("apply".bytes(), 10);
+/// let trie = builder.build_with(BuildOptions::new().counts().scores(|freq: &u32| *freq));
+/// assert_eq!(trie.count_prefix("app"), 2);
+/// ```
+pub struct BuildOptions<'a, Value> {
+    counts: bool,
+    scores: Option<Box<Compare<'a, Value>>>,
+    insertion_order: bool,
+}
+
+/// Orders two values by score.
+type Compare<'a, Value> = dyn Fn(&Value, &Value) -> Ordering + 'a;
+
+impl<'a, Value> fmt::Debug for BuildOptions<'a, Value> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BuildOptions")
+            .field("counts", &self.counts)
+            .field("scores", &self.scores.is_some())
+            .field("insertion_order", &self.insertion_order)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Where the entries of a [Trie] rank by score or insertion order, from 0 for
+/// the first, with ties going to the smaller node number.
+struct Ranks {
+    /// The rank of each value.
+    values: PackedInts,
+
+    /// The best rank in each node's subtree, indexed by node number, or the
+    /// number of entries if there is none.
+    subtrees: PackedInts,
 }
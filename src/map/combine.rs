@@ -0,0 +1,178 @@
+//! Operations that walk two tries in lockstep.
+use crate::map::trie_builder::from_parts;
+use crate::map::Trie;
+use fid::bit_vec;
+use louds::LoudsNodeNum;
+use std::cmp::Ordering;
+
+/// The nodes at the same key in two tries, if there is one.
+type NodePair = (Option<LoudsNodeNum>, Option<LoudsNodeNum>);
+
+/// A node of a combined trie, before the branches without any value are cut.
+struct Combined<Value> {
+    pair: NodePair,
+    parent: usize,
+    value: Option<Value>,
+}
+
+impl<Label: Ord, Value> Trie<Label, Value> {
+    /// Build the trie of the keys of `self` and `other` for which `merge`
+    /// returns a value. `merge` is called with the values of each key in
+    /// either trie, but keys only in `self` are left out unless `keep_left`,
+    /// and keys only in `other` unless `keep_right`.
+    ///
+    /// Both tries are walked breadth first, so the new trie comes out in node
+    /// order without going through a [crate::map::TrieBuilder].
+    pub(crate) fn combine<W, V>(
+        &self,
+        other: &Trie<Label, W>,
+        keep_left: bool,
+        keep_right: bool,
+        mut merge: impl FnMut(Option<&Value>, Option<&W>) -> Option<V>,
+    ) -> Trie<Label, V>
+    where
+        Label: Clone,
+    {
+        let root = LoudsNodeNum(1);
+        let mut nodes = vec![Combined {
+            pair: (Some(root), Some(root)),
+            parent: 0,
+            value: None,
+        }];
+        let mut children = Vec::new();
+        let mut i = 0;
+        while i < nodes.len() {
+            zip_children(self, other, nodes[i].pair, &mut children);
+            for &(a, b) in &children {
+                if (b.is_none() && !keep_left) || (a.is_none() && !keep_right) {
+                    continue;
+                }
+                let va = a.and_then(|a| self.value(a));
+                let vb = b.and_then(|b| other.value(b));
+                let value = if va.is_some() || vb.is_some() {
+                    merge(va, vb)
+                } else {
+                    None
+                };
+                nodes.push(Combined {
+                    pair: (a, b),
+                    parent: i,
+                    value,
+                });
+            }
+            i += 1;
+        }
+
+        // Children come after their parents, so one pass from the back finds
+        // every node with a value under it.
+        let mut alive: Vec<bool> = nodes.iter().map(|node| node.value.is_some()).collect();
+        alive[0] = true;
+        for i in (1..nodes.len()).rev() {
+            if alive[i] {
+                alive[nodes[i].parent] = true;
+            }
+        }
+
+        let mut louds_bits = bit_vec![true, false];
+        let mut labels: Vec<Label> = vec![];
+        let mut terminals = bit_vec![false, false];
+        let mut values: Vec<V> = vec![];
+        let mut child = 1;
+        for parent in (0..nodes.len()).filter(|&i| alive[i]) {
+            // The children of nodes that were cut are cut too.
+            while child < nodes.len() && nodes[child].parent <= parent {
+                if alive[child] {
+                    let label = match nodes[child].pair {
+                        (Some(a), _) => self.label(a),
+                        (None, b) => other.label(b.expect("Neither side of the pair")),
+                    };
+                    louds_bits.push(true);
+                    labels.push(label.clone());
+                    terminals.push(nodes[child].value.is_some());
+                    values.extend(nodes[child].value.take());
+                }
+                child += 1;
+            }
+            louds_bits.push(false);
+        }
+        from_parts(louds_bits, labels, terminals, values)
+    }
+
+    /// Return true if every key of `self` is a key of `other`.
+    pub(crate) fn keys_subset<W>(&self, other: &Trie<Label, W>) -> bool {
+        if self.len() > other.len() {
+            return false;
+        }
+        let root = LoudsNodeNum(1);
+        let mut stack = vec![(Some(root), Some(root))];
+        let mut children = Vec::new();
+        while let Some(pair) = stack.pop() {
+            zip_children(self, other, pair, &mut children);
+            for &(a, b) in &children {
+                match (a, b) {
+                    // Every leaf of a set trie is a key, so `a` has one below it.
+                    (Some(_), None) => return false,
+                    (Some(a), Some(b)) => {
+                        if self.is_terminal(a) && !other.is_terminal(b) {
+                            return false;
+                        }
+                        stack.push((Some(a), Some(b)));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+
+    /// Return true if `self` and `other` have no key in common.
+    pub(crate) fn keys_disjoint<W>(&self, other: &Trie<Label, W>) -> bool {
+        let root = LoudsNodeNum(1);
+        let mut stack = vec![(Some(root), Some(root))];
+        let mut children = Vec::new();
+        while let Some(pair) = stack.pop() {
+            zip_children(self, other, pair, &mut children);
+            for &(a, b) in &children {
+                if let (Some(a), Some(b)) = (a, b) {
+                    if self.is_terminal(a) && other.is_terminal(b) {
+                        return false;
+                    }
+                    stack.push((Some(a), Some(b)));
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Put the children of both nodes of `pair` into `out`, in label order, with
+/// the children that have the same label paired up.
+fn zip_children<Label: Ord, V, W>(
+    left: &Trie<Label, V>,
+    right: &Trie<Label, W>,
+    (a, b): NodePair,
+    out: &mut Vec<NodePair>,
+) {
+    out.clear();
+    let mut lefts = a
+        .into_iter()
+        .flat_map(|a| left.children_node_nums(a))
+        .peekable();
+    let mut rights = b
+        .into_iter()
+        .flat_map(|b| right.children_node_nums(b))
+        .peekable();
+    loop {
+        let order = match (lefts.peek(), rights.peek()) {
+            (Some(&x), Some(&y)) => left.label(x).cmp(right.label(y)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return,
+        };
+        out.push(match order {
+            Ordering::Less => (lefts.next(), None),
+            Ordering::Greater => (None, rights.next()),
+            Ordering::Equal => (lefts.next(), rights.next()),
+        });
+    }
+}
@@ -1,11 +1,25 @@
 //! A trie map stores a value with each word or key.
 use super::Trie;
+use crate::aho_corasick::{AhoCorasick, MatchKind};
+use crate::automaton::Automaton;
 use crate::inc_search::IncSearch;
-use crate::iter::{PostfixIter, PrefixIter, SearchIter};
+use crate::internal_data_structure::fnv::Fnv1a;
+use crate::iter::{
+    ArcPostfixIter, ArcSearchIter, AutomatonIter, FuzzyIter, PatternIter, PostfixIter, PrefixIter,
+    RangeIter, SearchIter,
+};
+use crate::node_ref::NodeRef;
+use crate::pattern::PatternElem;
+use crate::segment::{Segmentation, Segmenter};
 use crate::try_collect::{TryCollect, TryFromIterator};
 use fid::{BitVector, FID};
 use louds::{AncestorNodeIter, ChildNodeIter, LoudsNodeNum};
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::hash::Hasher;
 use std::iter::FromIterator;
+use std::ops::{Range, RangeBounds};
+use std::sync::Arc;
 
 impl<Label: Ord, Value> Trie<Label, Value> {
     /// Return `Some(&Value)` if query is an exact match.
@@ -20,20 +34,11 @@ impl<Label: Ord, Value> Trie<Label, Value> {
         let mut cur_node_num = LoudsNodeNum(1);
 
         for (i, chr) in query.as_ref().iter().enumerate() {
-            let children_node_nums: Vec<LoudsNodeNum> =
-                self.children_node_nums(cur_node_num).collect();
-            let res = self.bin_search_by_children_labels(chr, &children_node_nums[..]);
-
-            match res {
-                Ok(j) => {
-                    let child_node_num = children_node_nums[j];
-                    if i == query.as_ref().len() - 1 && self.is_terminal(child_node_num) {
-                        return Some(child_node_num);
-                    }
-                    cur_node_num = child_node_num;
-                }
-                Err(_) => return None,
+            let child_node_num = self.child(cur_node_num, chr)?;
+            if i == query.as_ref().len() - 1 && self.is_terminal(child_node_num) {
+                return Some(child_node_num);
             }
+            cur_node_num = child_node_num;
         }
         None
     }
@@ -44,12 +49,136 @@ impl<Label: Ord, Value> Trie<Label, Value> {
             .and_then(move |x| self.value_mut(x))
     }
 
+    /// Return the number of entries.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Return true if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Return the ID of `query` if it is an exact match.
+    ///
+    /// IDs are dense: every entry has a distinct ID in `0..len()`. They follow
+    /// the trie's internal (breadth-first) order rather than lexicographical
+    /// order, and are stable for a given set of keys. An entry's value is at
+    /// its ID in [values()][Trie::values].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Trie::from_iter([("a", 0), ("app", 1), ("apple", 2)].map(|(k, v)| (k.bytes(), v)));
+    /// let id = trie.key_id("app").unwrap();
+    /// assert!(id < trie.len());
+    /// assert_eq!(trie.key_by_id::<String, _>(id), Some("app".to_string()));
+    /// assert_eq!(trie.values()[id], 1);
+    /// assert_eq!(trie.key_id("ap"), None);
+    /// ```
+    pub fn key_id(&self, query: impl AsRef<[Label]>) -> Option<usize> {
+        self.exact_match_node(query)
+            .map(|node| self.terminals.rank1(node.0) as usize)
+    }
+
+    /// Return the key with the ID `id`, or `None` if `id >= len()`. See
+    /// [key_id()][Trie::key_id].
+    pub fn key_by_id<C, M>(&self, id: usize) -> Option<C>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.id_to_node(id).map(|node| self.key(node))
+    }
+
+    /// Return the values, indexed by key ID.
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    /// Return a trie with the keys of both `self` and `other`. The value of a
+    /// key in both is `merge` of its value in `self` and its value in `other`.
+    ///
+    /// Both tries are walked together in order, so this takes time linear in
+    /// their numbers of nodes rather than inserting every entry into a
+    /// [crate::map::TrieBuilder].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    ///
+    /// let monday = Trie::from_iter([("apple", 2), ("pear", 1)].map(|(k, v)| (k.bytes(), v)));
+    /// let tuesday = Trie::from_iter([("apple", 3), ("plum", 4)].map(|(k, v)| (k.bytes(), v)));
+    ///
+    /// let total = monday.merge_with(&tuesday, |a, b| a + b);
+    /// let entries: Vec<(String, &u32)> = total.iter().collect();
+    /// assert_eq!(
+    ///     entries,
+    ///     [("apple".into(), &5), ("pear".into(), &1), ("plum".into(), &4)]
+    /// );
+    /// ```
+    pub fn merge_with(&self, other: &Self, mut merge: impl FnMut(&Value, &Value) -> Value) -> Self
+    where
+        Label: Clone,
+        Value: Clone,
+    {
+        self.combine(other, true, true, |a, b| match (a, b) {
+            (Some(a), Some(b)) => Some(merge(a, b)),
+            (a, b) => a.or(b).cloned(),
+        })
+    }
+
+    /// Find the terminal node with the value index `id`.
+    fn id_to_node(&self, id: usize) -> Option<LoudsNodeNum> {
+        if id >= self.values.len() {
+            return None;
+        }
+        let id = id as u64;
+        // Find the last node with at most `id` terminals before it, which is
+        // the terminal we are after since the next node has `id + 1`.
+        let (mut lo, mut hi) = (2, self.labels.len() as u64 + 1);
+        while lo < hi {
+            let mid = lo + (hi - lo).div_ceil(2);
+            if self.terminals.rank1(mid) <= id {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        debug_assert!(self.terminals[lo]);
+        Some(LoudsNodeNum(lo))
+    }
+
+    /// Return the root node, to walk the trie by hand. See [crate::node_ref]
+    /// for details.
+    pub fn root(&self) -> NodeRef<'_, Label, Value> {
+        NodeRef::new(self, LoudsNodeNum(1))
+    }
+
     /// Create an incremental search. Useful for interactive applications. See
     /// [crate::inc_search] for details.
     pub fn inc_search(&self) -> IncSearch<'_, Label, Value> {
         IncSearch::new(self)
     }
 
+    /// Create an [AhoCorasick] matcher to find the keys inside a text. See
+    /// [crate::aho_corasick] for details.
+    pub fn aho_corasick(&self, kind: MatchKind) -> AhoCorasick<'_, Label, Value> {
+        AhoCorasick::new(self, kind)
+    }
+
+    /// Return the segmentation of `text` into keys with the lowest total
+    /// `cost`, or `None` if the keys cannot cover `text`. See
+    /// [crate::segment] for n-best segmentations and unknown labels.
+    pub fn segment<'a>(
+        &'a self,
+        text: impl AsRef<[Label]>,
+        cost: impl Fn(&Value) -> i64 + 'a,
+    ) -> Option<Segmentation<'a, Value>> {
+        Segmenter::new(self, cost).best(text)
+    }
+
     /// Return true if `query` is a prefix.
     ///
     /// Note: A prefix may be an exact match or not, and an exact match may be a
@@ -58,11 +187,9 @@ impl<Label: Ord, Value> Trie<Label, Value> {
         let mut cur_node_num = LoudsNodeNum(1);
 
         for chr in query.as_ref().iter() {
-            let children_node_nums: Vec<_> = self.children_node_nums(cur_node_num).collect();
-            let res = self.bin_search_by_children_labels(chr, &children_node_nums[..]);
-            match res {
-                Ok(j) => cur_node_num = children_node_nums[j],
-                Err(_) => return false,
+            match self.child(cur_node_num, chr) {
+                Some(child_node_num) => cur_node_num = child_node_num,
+                None => return false,
             }
         }
         // Are there more nodes after our query?
@@ -70,6 +197,10 @@ impl<Label: Ord, Value> Trie<Label, Value> {
     }
 
     /// Return all entries and their values that match `query`.
+    ///
+    /// Call `.rev()` on the iterator to get the entries in descending order.
+    /// The iterator knows how many entries are left, see
+    /// [count_prefix()][Trie::count_prefix].
     pub fn predictive_search<C, M>(
         &self,
         query: impl AsRef<[Label]>,
@@ -81,30 +212,368 @@ impl<Label: Ord, Value> Trie<Label, Value> {
         SearchIter::new(self, query)
     }
 
-    /// Return the postfixes and values of all entries that match `query`.
-    pub fn postfix_search<C, M>(
+    /// Return all entries and their values that match `query`, like
+    /// [predictive_search()][Trie::predictive_search], but from a trie behind
+    /// an [Arc].
+    ///
+    /// The iterator holds on to the [Arc] instead of borrowing the trie, so it
+    /// is `'static`, and `Send` if the trie is `Send` and `Sync`. It yields
+    /// clones of the values.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Arc::new(Trie::from_iter(
+    ///     [("app", 1), ("apple", 2), ("bet", 3)].map(|(k, v)| (k.bytes(), v)),
+    /// ));
+    /// let results = Arc::clone(&trie).into_predictive_search::<String, _>("app");
+    /// let handle = std::thread::spawn(move || results.collect::<Vec<_>>());
+    /// assert_eq!(handle.join().unwrap(), [("app".into(), 1), ("apple".into(), 2)]);
+    /// ```
+    pub fn into_predictive_search<C, M>(
+        self: Arc<Self>,
+        query: impl AsRef<[Label]>,
+    ) -> ArcSearchIter<Label, Value, C, M>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        ArcSearchIter::new(self, query)
+    }
+
+    /// Return the number of entries that start with `query`, without visiting
+    /// them.
+    ///
+    /// This takes time proportional to the length of `query` if the trie was
+    /// built with [BuildOptions::counts][crate::map::BuildOptions::counts], and
+    /// also proportional to the height of the trie otherwise.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::{BuildOptions, TrieBuilder};
+    ///
+    /// let mut builder = TrieBuilder::new();
+    /// for (i, key) in ["a", "app", "apple", "better"].into_iter().enumerate() {
+    ///     builder.insert(key.bytes(), i);
+    /// }
+    /// let trie = builder.build_with(BuildOptions::new().counts());
+    /// assert_eq!(trie.count_prefix("app"), 2);
+    /// assert_eq!(trie.count_prefix(""), 4);
+    /// assert_eq!(trie.count_prefix("c"), 0);
+    ///
+    /// let mut results = trie.predictive_search::<String, _>("a");
+    /// assert_eq!(results.len(), 3);
+    /// results.next();
+    /// assert_eq!(results.len(), 2);
+    /// ```
+    pub fn count_prefix(&self, query: impl AsRef<[Label]>) -> usize {
+        self.prefix_node(query)
+            .map_or(0, |node| self.subtree_count(node))
+    }
+
+    /// Return the `k` entries that start with `query` and have the highest
+    /// score, along with their values, from highest to lowest score. Entries
+    /// with equal scores come in no particular order.
+    ///
+    /// The scores are the ones given to
+    /// [BuildOptions::scores][crate::map::BuildOptions::scores] when the trie
+    /// was built. The search goes best first and only visits the subtrees
+    /// that can still make it into the results. Return `None` if the trie was
+    /// built without scores; use
+    /// [top_k_predictive_by()][Trie::top_k_predictive_by] for those.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::{BuildOptions, TrieBuilder};
+    ///
+    /// let mut builder = TrieBuilder::new();
+    /// builder.insert("apple".bytes(), 30);
+    /// builder.insert("application".bytes(), 50);
+    /// builder.insert("apply".bytes(), 10);
+    /// builder.insert("banana".bytes(), 90);
+    /// let trie = builder.build_with(BuildOptions::new().scores(|freq: &u32| *freq));
+    ///
+    /// let results: Vec<(String, &u32)> = trie.top_k_predictive("app", 2).unwrap();
+    /// assert_eq!(
+    ///     results,
+    ///     [("application".to_string(), &50), ("apple".to_string(), &30)]
+    /// );
+    ///
+    /// let trie = TrieBuilder::<u8, u32>::new().build();
+    /// assert_eq!(trie.top_k_predictive::<String, _>("app", 2), None);
+    /// ```
+    pub fn top_k_predictive<C, M>(
         &self,
         query: impl AsRef<[Label]>,
-    ) -> PostfixIter<'_, Label, Value, C, M>
+        k: usize,
+    ) -> Option<Vec<(C, &Value)>>
     where
         C: TryFromIterator<Label, M>,
         Label: Clone,
     {
-        let mut cur_node_num = LoudsNodeNum(1);
+        let ranks = self.score_ranks.as_ref()?;
+        let node = match self.prefix_node(query) {
+            Some(node) if k > 0 && !self.is_empty() => node,
+            _ => return Some(Vec::new()),
+        };
+        // `(rank, is an entry, node number)`, where a subtree ranks as its
+        // best entry.
+        let rank = ranks.subtrees.get(node.0 as usize);
+        let mut heap = BinaryHeap::new();
+        heap.push(Reverse((rank, false, node.0)));
+        let mut results = Vec::with_capacity(k);
+        while let Some(Reverse((_, is_entry, node_num))) = heap.pop() {
+            let node = LoudsNodeNum(node_num);
+            if is_entry {
+                results.push(self.key_value(node));
+                if results.len() == k {
+                    break;
+                }
+                continue;
+            }
+            if self.is_terminal(node) {
+                let rank = ranks.values.get(self.terminals.rank1(node_num) as usize);
+                heap.push(Reverse((rank, true, node_num)));
+            }
+            for child in self.children_node_nums(node) {
+                let rank = ranks.subtrees.get(child.0 as usize);
+                heap.push(Reverse((rank, false, child.0)));
+            }
+        }
+        Some(results)
+    }
 
-        // Consumes query (prefix)
-        for chr in query.as_ref() {
-            let children_node_nums: Vec<_> = self.children_node_nums(cur_node_num).collect();
-            let res = self.bin_search_by_children_labels(chr, &children_node_nums[..]);
-            match res {
-                Ok(i) => cur_node_num = children_node_nums[i],
-                Err(_) => {
-                    return PostfixIter::empty(self);
+    /// Return the `k` entries that start with `query` and have the highest
+    /// `score`, along with their values, from highest to lowest score.
+    ///
+    /// Every entry that starts with `query` is scored. Entries with equal
+    /// scores come in no particular order. See
+    /// [top_k_predictive()][Trie::top_k_predictive] for a search that skips
+    /// the subtrees that cannot make it into the results.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Trie::from_iter(
+    ///     [("apple", 30), ("application", 50), ("apply", 10), ("banana", 90)]
+    ///         .map(|(k, v)| (k.bytes(), v)),
+    /// );
+    /// let results: Vec<(String, &u32)> = trie.top_k_predictive_by("app", 2, |freq| *freq);
+    /// assert_eq!(
+    ///     results,
+    ///     [("application".to_string(), &50), ("apple".to_string(), &30)]
+    /// );
+    /// ```
+    pub fn top_k_predictive_by<C, M, S: Ord>(
+        &self,
+        query: impl AsRef<[Label]>,
+        k: usize,
+        score: impl Fn(&Value) -> S,
+    ) -> Vec<(C, &Value)>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        let node = match self.prefix_node(query) {
+            Some(node) if k > 0 => node,
+            _ => return Vec::new(),
+        };
+        self.top_k_exhaustive(node, k, score)
+            .into_iter()
+            .map(|node| self.key_value(LoudsNodeNum(node)))
+            .collect()
+    }
+
+    /// Score every entry under `node`, keeping the best `k` in a heap.
+    fn top_k_exhaustive<S: Ord>(
+        &self,
+        node: LoudsNodeNum,
+        k: usize,
+        score: impl Fn(&Value) -> S,
+    ) -> Vec<u64> {
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            if let Some(v) = self.value(node) {
+                heap.push(Reverse((score(v), node.0)));
+                if heap.len() > k {
+                    heap.pop();
                 }
             }
+            stack.extend(self.children_node_nums(node));
         }
+        let mut results = heap.into_vec();
+        results.sort();
+        results.into_iter().map(|Reverse((_, node))| node).collect()
+    }
+
+    /// Return all entries and their values within `max_edits` edits of `query`,
+    /// along with their Levenshtein distance to `query`.
+    ///
+    /// Subtrees that cannot come within `max_edits` of `query` are never visited.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Trie::from_iter(
+    ///     [("apple", 0), ("apply", 1), ("ample", 2), ("banana", 3)]
+    ///         .into_iter()
+    ///         .map(|e| (e.0.bytes(), e.1))
+    /// );
+    /// let results: Vec<(String, &u8, usize)> = trie.fuzzy_search("appke", 1).collect();
+    /// assert_eq!(results, [("apple".to_string(), &0u8, 1)]);
+    /// ```
+    pub fn fuzzy_search<C, M>(
+        &self,
+        query: impl AsRef<[Label]>,
+        max_edits: usize,
+    ) -> FuzzyIter<'_, Label, Value, C, M>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        FuzzyIter::new(self, query, max_edits, false)
+    }
+
+    /// Return all entries and their values that have a prefix within
+    /// `max_edits` edits of `query`, along with the smallest such distance.
+    ///
+    /// This is a typo-tolerant [Trie::predictive_search].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Trie::from_iter(
+    ///     [("apple", 0), ("application", 1), ("banana", 2)]
+    ///         .into_iter()
+    ///         .map(|e| (e.0.bytes(), e.1))
+    /// );
+    /// let results: Vec<(String, &u8, usize)> = trie.fuzzy_predictive_search("apl", 1).collect();
+    /// assert_eq!(
+    ///     results,
+    ///     [("apple".to_string(), &0u8, 1), ("application".to_string(), &1u8, 1)]
+    /// );
+    /// ```
+    pub fn fuzzy_predictive_search<C, M>(
+        &self,
+        query: impl AsRef<[Label]>,
+        max_edits: usize,
+    ) -> FuzzyIter<'_, Label, Value, C, M>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        FuzzyIter::new(self, query, max_edits, true)
+    }
 
-        PostfixIter::new(self, cur_node_num)
+    /// Return all entries and their values that match `pattern`.
+    ///
+    /// Only children whose labels satisfy the current [PatternElem]s are
+    /// visited. See [crate::pattern] for details.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    /// use trie::pattern::glob;
+    ///
+    /// let trie = Trie::from_iter(
+    ///     [("cat", 0), ("cot", 1), ("coat", 2), ("dog", 3)]
+    ///         .into_iter()
+    ///         .map(|e| (e.0.bytes(), e.1))
+    /// );
+    /// let results: Vec<(String, &u8)> = trie.pattern_search(glob("c*t")).collect();
+    /// assert_eq!(
+    ///     results,
+    ///     [("cat".to_string(), &0u8), ("coat".to_string(), &2u8), ("cot".to_string(), &1u8)]
+    /// );
+    /// ```
+    pub fn pattern_search<C, M>(
+        &self,
+        pattern: impl AsRef<[PatternElem<Label>]>,
+    ) -> PatternIter<'_, Label, Value, C, M>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        PatternIter::new(self, pattern)
+    }
+
+    /// Return all entries and their values that `aut` matches.
+    ///
+    /// Subtrees where [Automaton::can_match] is false are never visited. See
+    /// [crate::automaton] for details.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    /// use trie::automaton::Str;
+    ///
+    /// let trie = Trie::from_iter(
+    ///     [("apple", 0), ("apply", 1)]
+    ///         .into_iter()
+    ///         .map(|e| (e.0.bytes(), e.1))
+    /// );
+    /// let results: Vec<(String, &u8)> = trie.search_automaton(Str::new("apply")).collect();
+    /// assert_eq!(results, [("apply".to_string(), &1u8)]);
+    /// ```
+    pub fn search_automaton<A, C, M>(&self, aut: A) -> AutomatonIter<'_, Label, Value, A, C, M>
+    where
+        A: Automaton<Label>,
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        AutomatonIter::new(self, aut)
+    }
+
+    /// Return the postfixes and values of all entries that match `query`.
+    pub fn postfix_search<C, M>(
+        &self,
+        query: impl AsRef<[Label]>,
+    ) -> PostfixIter<'_, Label, Value, C, M>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        match self.prefix_node(query) {
+            Some(node_num) => PostfixIter::new(self, node_num),
+            None => PostfixIter::empty(self),
+        }
+    }
+
+    /// Return the postfixes and values of all entries that match `query`, like
+    /// [postfix_search()][Trie::postfix_search], but from a trie behind an
+    /// [Arc].
+    ///
+    /// The iterator holds on to the [Arc] instead of borrowing the trie, and
+    /// yields clones of the values.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Arc::new(Trie::from_iter(
+    ///     [("app", 1), ("apple", 2), ("apply", 3)].map(|(k, v)| (k.bytes(), v)),
+    /// ));
+    /// let results = Arc::clone(&trie).into_postfix_search::<String, _>("app");
+    /// let handle = std::thread::spawn(move || results.rev().collect::<Vec<_>>());
+    /// assert_eq!(handle.join().unwrap(), [("ly".into(), 3), ("le".into(), 2)]);
+    /// ```
+    pub fn into_postfix_search<C, M>(
+        self: Arc<Self>,
+        query: impl AsRef<[Label]>,
+    ) -> ArcPostfixIter<Label, Value, C, M>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        let root = self.prefix_node(query);
+        ArcPostfixIter::new(self, root)
     }
 
     /// Returns an iterator across all keys in the trie.
@@ -142,6 +611,304 @@ impl<Label: Ord, Value> Trie<Label, Value> {
         self.postfix_search([])
     }
 
+    /// Return all entries and their values whose keys fall within `range`, in
+    /// lexicographical order.
+    ///
+    /// The iteration starts directly at the lower bound and stops at the upper
+    /// bound, without visiting the rest of the trie.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Trie::from_iter(
+    ///     [("2024-01", 0), ("2024-02", 1), ("2024-03", 2), ("2025-01", 3)]
+    ///         .into_iter()
+    ///         .map(|e| (e.0.bytes(), e.1))
+    /// );
+    /// let results: Vec<(String, &u8)> = trie.range("2024-02".."2025").collect();
+    /// assert_eq!(
+    ///     results,
+    ///     [("2024-02".to_string(), &1u8), ("2024-03".to_string(), &2u8)]
+    /// );
+    /// ```
+    pub fn range<C, M, K>(&self, range: impl RangeBounds<K>) -> RangeIter<'_, Label, Value, C, M>
+    where
+        C: TryFromIterator<Label, M>,
+        K: AsRef<[Label]> + ?Sized,
+        Label: Clone,
+    {
+        RangeIter::new(self, range)
+    }
+
+    /// Return the smallest entry and its value.
+    pub fn first<C, M>(&self) -> Option<(C, &Value)>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.successor_node(&[], true)
+            .map(|node| self.key_value(node))
+    }
+
+    /// Return the largest entry and its value.
+    pub fn last<C, M>(&self) -> Option<(C, &Value)>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.rightmost_terminal(LoudsNodeNum(1))
+            .map(|node| self.key_value(node))
+    }
+
+    /// Return the largest entry less than or equal to `query`, and its value.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Trie::from_iter(
+    ///     [("apple", 0), ("banana", 1), ("cherry", 2)]
+    ///         .into_iter()
+    ///         .map(|e| (e.0.bytes(), e.1))
+    /// );
+    /// assert_eq!(trie.floor("blueberry"), Some(("banana".to_string(), &1)));
+    /// assert_eq!(trie.floor("banana"), Some(("banana".to_string(), &1)));
+    /// assert_eq!(trie.floor::<String, _>("aardvark"), None);
+    /// ```
+    pub fn floor<C, M>(&self, query: impl AsRef<[Label]>) -> Option<(C, &Value)>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.predecessor_node(query.as_ref(), true)
+            .map(|node| self.key_value(node))
+    }
+
+    /// Return the smallest entry greater than or equal to `query`, and its
+    /// value.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Trie::from_iter(
+    ///     [("apple", 0), ("banana", 1), ("cherry", 2)]
+    ///         .into_iter()
+    ///         .map(|e| (e.0.bytes(), e.1))
+    /// );
+    /// assert_eq!(trie.ceiling("blueberry"), Some(("cherry".to_string(), &2)));
+    /// assert_eq!(trie.ceiling("banana"), Some(("banana".to_string(), &1)));
+    /// assert_eq!(trie.ceiling::<String, _>("date"), None);
+    /// ```
+    pub fn ceiling<C, M>(&self, query: impl AsRef<[Label]>) -> Option<(C, &Value)>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.successor_node(query.as_ref(), true)
+            .map(|node| self.key_value(node))
+    }
+
+    /// Return the smallest entry strictly greater than `query`, and its value.
+    pub fn next_after<C, M>(&self, query: impl AsRef<[Label]>) -> Option<(C, &Value)>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.successor_node(query.as_ref(), false)
+            .map(|node| self.key_value(node))
+    }
+
+    /// Return the largest entry strictly less than `query`, and its value.
+    pub fn prev_before<C, M>(&self, query: impl AsRef<[Label]>) -> Option<(C, &Value)>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.predecessor_node(query.as_ref(), false)
+            .map(|node| self.key_value(node))
+    }
+
+    /// Return the number of entries lexicographically less than `query`.
+    ///
+    /// `query` need not be an entry. If the trie was built with
+    /// [BuildOptions::counts][crate::map::BuildOptions::counts], this takes
+    /// time proportional to the length of `query` times the number of children
+    /// along its path. Otherwise the entries under each of those children are
+    /// counted one level at a time, which multiplies that by the height of the
+    /// trie and a binary search over the nodes.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Trie::from_iter([("a", 0), ("app", 1), ("apple", 2), ("better", 3)].map(|(k, v)| (k.bytes(), v)));
+    /// assert_eq!(trie.rank("a"), 0);
+    /// assert_eq!(trie.rank("apple"), 2);
+    /// assert_eq!(trie.rank("b"), 3);
+    /// assert_eq!(trie.rank("z"), 4);
+    /// ```
+    pub fn rank(&self, query: impl AsRef<[Label]>) -> usize {
+        let mut cur_node_num = LoudsNodeNum(1);
+        let mut rank = 0;
+        for chr in query.as_ref() {
+            // A proper prefix of `query` is less than it.
+            rank += usize::from(self.is_terminal(cur_node_num));
+            let mut next = None;
+            for child in self.children_node_nums(cur_node_num) {
+                match self.label(child).cmp(chr) {
+                    Ordering::Less => rank += self.subtree_count(child),
+                    Ordering::Equal => {
+                        next = Some(child);
+                        break;
+                    }
+                    Ordering::Greater => break,
+                }
+            }
+            match next {
+                Some(child) => cur_node_num = child,
+                None => break,
+            }
+        }
+        rank
+    }
+
+    /// Return the `n`th entry in lexicographical order, counting from zero, or
+    /// `None` if `n >= len()`.
+    ///
+    /// Like [rank()][Trie::rank], this counts the entries under the children
+    /// along the way, which is only quick if the trie was built with
+    /// [BuildOptions::counts][crate::map::BuildOptions::counts].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    ///
+    /// let trie = Trie::from_iter([("a", 0), ("app", 1), ("apple", 2), ("better", 3)].map(|(k, v)| (k.bytes(), v)));
+    /// assert_eq!(trie.nth(2), Some(("apple".to_string(), &2)));
+    /// assert_eq!(trie.nth::<String, _>(4), None);
+    /// ```
+    pub fn nth<C, M>(&self, n: usize) -> Option<(C, &Value)>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        if n >= self.len() {
+            return None;
+        }
+        let mut cur_node_num = LoudsNodeNum(1);
+        let mut n = n;
+        loop {
+            if self.is_terminal(cur_node_num) {
+                if n == 0 {
+                    return Some(self.key_value(cur_node_num));
+                }
+                n -= 1;
+            }
+            // Find the child whose subtree holds the `n`th remaining entry.
+            for child in self.children_node_nums(cur_node_num) {
+                let count = self.subtree_count(child);
+                if n < count {
+                    cur_node_num = child;
+                    break;
+                }
+                n -= count;
+            }
+        }
+    }
+
+    /// Return the first terminal at or after `query` in lexicographical
+    /// order, skipping `query` itself unless `inclusive`.
+    fn successor_node(&self, query: &[Label], inclusive: bool) -> Option<LoudsNodeNum> {
+        let mut cur_node_num = LoudsNodeNum(1);
+        // The smallest subtree branching off `query` with a greater label.
+        let mut candidate = None;
+
+        for chr in query {
+            let children = self.children_range(cur_node_num);
+            match self.bin_search_by_children_labels(chr, children.clone()) {
+                Ok(child) => {
+                    if child + 1 < children.end {
+                        candidate = Some(LoudsNodeNum(child + 1));
+                    }
+                    cur_node_num = LoudsNodeNum(child);
+                }
+                Err(child) => {
+                    return (child < children.end)
+                        .then_some(LoudsNodeNum(child))
+                        .or(candidate)
+                        .and_then(|node| self.leftmost_terminal(node));
+                }
+            }
+        }
+
+        if inclusive && self.is_terminal(cur_node_num) {
+            return Some(cur_node_num);
+        }
+        // Anything below `query` comes before its greater siblings.
+        self.children_node_nums(cur_node_num)
+            .next()
+            .or(candidate)
+            .and_then(|node| self.leftmost_terminal(node))
+    }
+
+    /// Return the last terminal at or before `query` in lexicographical
+    /// order, skipping `query` itself unless `inclusive`.
+    fn predecessor_node(&self, query: &[Label], inclusive: bool) -> Option<LoudsNodeNum> {
+        let mut cur_node_num = LoudsNodeNum(1);
+        // The largest entry before `query` found so far: either a whole
+        // subtree branching off with a smaller label, or a prefix of `query`.
+        let mut candidate: Option<(LoudsNodeNum, bool)> = None;
+        let mut found = true;
+
+        for chr in query {
+            let children = self.children_range(cur_node_num);
+            let res = self.bin_search_by_children_labels(chr, children.clone());
+            let child = match res {
+                Ok(child) | Err(child) => child,
+            };
+            if child > children.start {
+                candidate = Some((LoudsNodeNum(child - 1), true));
+            } else if self.is_terminal(cur_node_num) {
+                candidate = Some((cur_node_num, false));
+            }
+            match res {
+                Ok(child) => cur_node_num = LoudsNodeNum(child),
+                Err(_) => {
+                    found = false;
+                    break;
+                }
+            }
+        }
+
+        if found && inclusive && self.is_terminal(cur_node_num) {
+            return Some(cur_node_num);
+        }
+        match candidate {
+            Some((node, true)) => self.rightmost_terminal(node),
+            Some((node, false)) => Some(node),
+            None => None,
+        }
+    }
+
+    /// Return the first terminal in the subtree of `node`.
+    fn leftmost_terminal(&self, mut node: LoudsNodeNum) -> Option<LoudsNodeNum> {
+        while !self.is_terminal(node) {
+            node = self.children_node_nums(node).next()?;
+        }
+        Some(node)
+    }
+
+    /// Return the last terminal in the subtree of `node`.
+    fn rightmost_terminal(&self, mut node: LoudsNodeNum) -> Option<LoudsNodeNum> {
+        while let Some(child) = self.children_node_nums(node).next_back() {
+            node = child;
+        }
+        // Every leaf is a terminal, except the root of an empty trie.
+        self.value(node).map(|_| node)
+    }
+
     /// Return the common prefixes of `query`.
     pub fn common_prefix_search<C, M>(
         &self,
@@ -154,7 +921,34 @@ impl<Label: Ord, Value> Trie<Label, Value> {
         PrefixIter::new(self, query)
     }
 
+    /// Return the longest key that `query` starts with, as the number of
+    /// labels it takes up, and its value.
+    ///
+    /// Unlike [longest_prefix()][Trie::longest_prefix], this never looks past
+    /// the end of `query`, and does not build the key.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::map::Trie;
+    ///
+    /// let routes = Trie::from_iter(
+    ///     [("/", 0), ("/api/", 1), ("/api/v2/", 2)].map(|(k, v)| (k.bytes(), v)),
+    /// );
+    /// assert_eq!(routes.longest_match("/api/v2/users"), Some((8, &2)));
+    /// assert_eq!(routes.longest_match("/api/v1/users"), Some((5, &1)));
+    /// assert_eq!(routes.longest_match("/about"), Some((1, &0)));
+    /// assert_eq!(routes.longest_match("about"), None);
+    /// ```
+    pub fn longest_match(&self, query: impl AsRef<[Label]>) -> Option<(usize, &Value)> {
+        self.longest_match_node(LoudsNodeNum(1), query.as_ref())
+            .map(|(len, node)| (len, self.value(node).expect("Not a terminal")))
+    }
+
     /// Return the longest shared prefix or terminal of `query`.
+    ///
+    /// This goes on past `query` as long as there is only one way to go. To
+    /// find the longest key that `query` starts with, see
+    /// [longest_match()][Trie::longest_match].
     pub fn longest_prefix<C, M>(&self, query: impl AsRef<[Label]>) -> Option<C>
     where
         C: TryFromIterator<Label, M>,
@@ -165,17 +959,8 @@ impl<Label: Ord, Value> Trie<Label, Value> {
 
         // Consumes query (prefix)
         for chr in query.as_ref() {
-            let children_node_nums: Vec<_> = self.children_node_nums(cur_node_num).collect();
-            let res = self.bin_search_by_children_labels(chr, &children_node_nums[..]);
-            match res {
-                Ok(i) => {
-                    cur_node_num = children_node_nums[i];
-                    buffer.push(cur_node_num);
-                }
-                Err(_) => {
-                    return None;
-                }
-            }
+            cur_node_num = self.child(cur_node_num, chr)?;
+            buffer.push(cur_node_num);
         }
 
         // Walk the trie as long as there is only one path and it isn't a terminal value.
@@ -204,6 +989,128 @@ impl<Label: Ord, Value> Trie<Label, Value> {
         }
     }
 
+    /// Return the key leading to `node_num`.
+    pub(crate) fn key<C, M>(&self, node_num: LoudsNodeNum) -> C
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        let mut v: Vec<Label> = self
+            .child_to_ancestors(node_num)
+            .map(|node| self.label(node).clone())
+            .collect();
+        v.reverse();
+        v.into_iter().try_collect().expect("Could not collect")
+    }
+
+    /// Return the key and value of the terminal `node_num`.
+    pub(crate) fn key_value<C, M>(&self, node_num: LoudsNodeNum) -> (C, &Value)
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        let value = self.value(node_num).expect("Not a terminal");
+        (self.key(node_num), value)
+    }
+
+    /// Return the length and node of the longest key that `query` starts
+    /// with, walking down from `node_num`.
+    pub(crate) fn longest_match_node(
+        &self,
+        node_num: LoudsNodeNum,
+        query: &[Label],
+    ) -> Option<(usize, LoudsNodeNum)> {
+        let mut cur_node_num = node_num;
+        let mut longest = None;
+        for (i, chr) in query.iter().enumerate() {
+            match self.child(cur_node_num, chr) {
+                Some(child_node_num) => cur_node_num = child_node_num,
+                None => break,
+            }
+            if self.is_terminal(cur_node_num) {
+                longest = Some((i + 1, cur_node_num));
+            }
+        }
+        longest
+    }
+
+    /// Return the number of nodes, the root included.
+    pub(crate) fn num_nodes(&self) -> usize {
+        self.labels.len() + 1
+    }
+
+    /// Return the number of terminals in the subtree of `node_num`.
+    pub(crate) fn subtree_count(&self, node_num: LoudsNodeNum) -> usize {
+        match &self.subtree_counts {
+            Some(counts) => counts.get(node_num.0 as usize),
+            None if node_num.0 == 1 => self.values.len(),
+            None => self.count_subtree(node_num),
+        }
+    }
+
+    /// Return where the entry of the terminal `node_num` ranks in insertion
+    /// order, if the trie was built with
+    /// [BuildOptions::insertion_order][crate::map::BuildOptions::insertion_order].
+    pub(crate) fn insertion_rank(&self, node_num: LoudsNodeNum) -> Option<usize> {
+        let ranks = self.insertion_ranks.as_ref()?;
+        Some(ranks.values.get(self.terminals.rank1(node_num.0) as usize))
+    }
+
+    /// Return the earliest insertion rank in the subtree of `node_num`, if the
+    /// trie was built with
+    /// [BuildOptions::insertion_order][crate::map::BuildOptions::insertion_order].
+    pub(crate) fn first_insertion_rank(&self, node_num: LoudsNodeNum) -> Option<usize> {
+        let ranks = self.insertion_ranks.as_ref()?;
+        Some(ranks.subtrees.get(node_num.0 as usize))
+    }
+
+    /// Count the terminals in the subtree of `node_num` one level at a time.
+    /// The descendants of a node on any one level have contiguous node
+    /// numbers, so each level only takes a rank and two binary searches.
+    fn count_subtree(&self, node_num: LoudsNodeNum) -> usize {
+        let (mut lo, mut hi) = (node_num.0, node_num.0);
+        let mut count = 0;
+        loop {
+            count += (self.terminals.rank1(hi) - self.terminals.rank1(lo)) as usize;
+            count += usize::from(self.terminals[hi]);
+
+            // The next level holds the nodes whose parents are in `lo..=hi`.
+            let first = self.partition_nodes(hi + 1, |parent| parent < lo);
+            let end = self.partition_nodes(first, |parent| parent <= hi);
+            if first == end {
+                return count;
+            }
+            lo = first;
+            hi = end - 1;
+        }
+    }
+
+    /// Return the first node number from `from` on whose parent's node number
+    /// does not satisfy `pred`. Parents never decrease with node numbers, so
+    /// `pred` must hold for a prefix of the nodes.
+    fn partition_nodes(&self, from: u64, pred: impl Fn(u64) -> bool) -> u64 {
+        let (mut lo, mut hi) = (from, self.labels.len() as u64 + 2);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let index = self.louds.node_num_to_index(LoudsNodeNum(mid));
+            if pred(self.louds.child_to_parent(index).0) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    /// Return the node `query` leads to, whether it is a terminal or not.
+    pub(crate) fn prefix_node(&self, query: impl AsRef<[Label]>) -> Option<LoudsNodeNum> {
+        let mut cur_node_num = LoudsNodeNum(1);
+        for chr in query.as_ref() {
+            cur_node_num = self.child(cur_node_num, chr)?;
+        }
+        Some(cur_node_num)
+    }
+
     pub(crate) fn has_children_node_nums(&self, node_num: LoudsNodeNum) -> bool {
         self.louds
             .parent_to_children_indices(node_num)
@@ -215,12 +1122,41 @@ impl<Label: Ord, Value> Trie<Label, Value> {
         self.louds.parent_to_children_nodes(node_num)
     }
 
+    /// Return the node numbers of the children of `node_num`. Children are
+    /// numbered one after the other, so this is all it takes to search them
+    /// without collecting them.
+    pub(crate) fn children_range(&self, node_num: LoudsNodeNum) -> Range<u64> {
+        let mut indices = self.louds.parent_to_children_indices(node_num);
+        match indices.next() {
+            Some(first) => {
+                let last = indices.next_back().map_or(first.0, |last| last.0);
+                let start = self.louds.index_to_node_num(first).0;
+                start..start + (last - first.0) + 1
+            }
+            None => 2..2,
+        }
+    }
+
+    /// Binary search the labels of the `children` of a node for `query`.
+    /// Return the node number of the child with that label, or else the one
+    /// where it would go, like [slice::binary_search].
     pub(crate) fn bin_search_by_children_labels(
         &self,
         query: &Label,
-        children_node_nums: &[LoudsNodeNum],
-    ) -> Result<usize, usize> {
-        children_node_nums.binary_search_by(|child_node_num| self.label(*child_node_num).cmp(query))
+        children: Range<u64>,
+    ) -> Result<u64, u64> {
+        let labels = &self.labels[(children.start - 2) as usize..(children.end - 2) as usize];
+        match labels.binary_search(query) {
+            Ok(j) => Ok(children.start + j as u64),
+            Err(j) => Err(children.start + j as u64),
+        }
+    }
+
+    /// Return the child of `node_num` with the label `query`.
+    pub(crate) fn child(&self, node_num: LoudsNodeNum, query: &Label) -> Option<LoudsNodeNum> {
+        self.bin_search_by_children_labels(query, self.children_range(node_num))
+            .ok()
+            .map(LoudsNodeNum)
     }
 
     pub(crate) fn label(&self, node_num: LoudsNodeNum) -> &Label {
@@ -252,6 +1188,41 @@ impl<Label: Ord, Value> Trie<Label, Value> {
     pub(crate) fn child_to_ancestors(&self, node_num: LoudsNodeNum) -> AncestorNodeIter<BitVector> {
         self.louds.child_to_ancestors(node_num)
     }
+
+    /// Return the parent of `node_num`, or `None` for the root.
+    pub(crate) fn parent_node(&self, node_num: LoudsNodeNum) -> Option<LoudsNodeNum> {
+        (node_num.0 > 1).then(|| {
+            self.louds
+                .child_to_parent(self.louds.node_num_to_index(node_num))
+        })
+    }
+}
+
+impl<Label, Value> Trie<Label, Value> {
+    /// Return the hash of the shape of the trie, working it out on the first
+    /// call. Unlike the methods above, it does not need `Label: Ord`, so that
+    /// any [crate::inc_search::IncSearch] converts into a
+    /// [crate::inc_search::Position].
+    ///
+    /// It hashes the number of children and the terminal flag of each node,
+    /// which together pin down the shape, so the first call takes time
+    /// proportional to the number of nodes.
+    pub(crate) fn fingerprint(&self) -> u64 {
+        *self.fingerprint.get_or_init(|| {
+            let num_nodes = self.labels.len() as u64 + 1;
+            let mut hasher = Fnv1a::default();
+            hasher.write_u64(num_nodes);
+            for node in 1..=num_nodes {
+                let degree = self
+                    .louds
+                    .parent_to_children_indices(LoudsNodeNum(node))
+                    .count();
+                hasher.write_u64(degree as u64);
+                hasher.write_u8(u8::from(self.terminals[node]));
+            }
+            hasher.finish()
+        })
+    }
 }
 
 impl<Label, Value, Key> FromIterator<(Key, Value)> for Trie<Label, Value>
@@ -273,10 +1244,15 @@ where
 
 #[cfg(test)]
 mod search_tests {
-    use crate::map::{Trie, TrieBuilder};
+    use crate::map::{BuildOptions, Trie, TrieBuilder};
     use std::iter::FromIterator;
+    use std::sync::Arc;
 
     fn build_trie() -> Trie<u8, u8> {
+        trie_builder().build()
+    }
+
+    fn trie_builder() -> TrieBuilder<u8, u8> {
         let mut builder = TrieBuilder::new();
         builder.insert("a".bytes(), 0);
         builder.insert("app".bytes(), 1);
@@ -284,7 +1260,7 @@ mod search_tests {
         builder.insert("better".bytes(), 3);
         builder.insert("application".bytes(), 4);
         builder.insert("アップル🍎".bytes(), 5);
-        builder.build()
+        builder
     }
 
     fn build_trie2() -> Trie<char, u8> {
@@ -535,6 +1511,625 @@ mod search_tests {
         }
     }
 
+    mod fuzzy_search_tests {
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (query, max_edits, expected_results) = $value;
+                    let trie = super::build_trie();
+                    let results: Vec<(String, &u8, usize)> = trie.fuzzy_search(query, max_edits).collect();
+                    let expected_results: Vec<(String, &u8, usize)> = expected_results.iter().map(|s| (s.0.to_string(), &s.1, s.2)).collect();
+                    assert_eq!(results, expected_results);
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: ("apple", 0, vec![("apple", 2, 0)]),
+            t2: ("apple", 1, vec![("apple", 2, 0)]),
+            t3: ("apple", 2, vec![("app", 1, 2), ("apple", 2, 0)]),
+            t4: ("appel", 2, vec![("app", 1, 2), ("apple", 2, 2)]),
+            t5: ("bitter", 1, vec![("better", 3, 1)]),
+            t6: ("", 1, vec![("a", 0, 1)]),
+            t7: ("zzz", 1, Vec::<(&str, u8, usize)>::new()),
+            t8: ("b", 0, Vec::<(&str, u8, usize)>::new()),
+        }
+    }
+
+    mod fuzzy_predictive_search_tests {
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (query, max_edits, expected_results) = $value;
+                    let trie = super::build_trie();
+                    let results: Vec<(String, &u8, usize)> = trie.fuzzy_predictive_search(query, max_edits).collect();
+                    let expected_results: Vec<(String, &u8, usize)> = expected_results.iter().map(|s| (s.0.to_string(), &s.1, s.2)).collect();
+                    assert_eq!(results, expected_results);
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: ("app", 0, vec![("app", 1, 0), ("apple", 2, 0), ("application", 4, 0)]),
+            t2: ("appel", 1, vec![("apple", 2, 1), ("application", 4, 1)]),
+            t3: ("btt", 1, vec![("better", 3, 1)]),
+            t4: ("bpp", 1, vec![("app", 1, 1), ("apple", 2, 1), ("application", 4, 1)]),
+            t5: ("", 0, vec![("a", 0, 0), ("app", 1, 0), ("apple", 2, 0), ("application", 4, 0), ("better", 3, 0), ("アップル🍎", 5, 0)]),
+            t6: ("zzz", 1, Vec::<(&str, u8, usize)>::new()),
+        }
+    }
+
+    #[test]
+    fn fuzzy_search_char() {
+        let trie = build_trie2();
+        let query: Vec<char> = "アッフル🍎".chars().collect();
+        let results: Vec<(String, &u8, usize)> = trie.fuzzy_search(query, 1).collect();
+        assert_eq!(results, [("アップル🍎".to_string(), &5, 1)]);
+    }
+
+    mod pattern_search_tests {
+        use crate::pattern::{glob, PatternElem::*};
+
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (pattern, expected_results) = $value;
+                    let trie = super::build_trie();
+                    let results: Vec<(String, &u8)> = trie.pattern_search(pattern).collect();
+                    let expected_results: Vec<(String, &u8)> = expected_results.iter().map(|s| (s.0.to_string(), &s.1)).collect();
+                    assert_eq!(results, expected_results);
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: (glob("a?p"), vec![("app", 1)]),
+            t2: (glob("app*"), vec![("app", 1), ("apple", 2), ("application", 4)]),
+            t3: (glob("*e"), vec![("apple", 2)]),
+            t4: (glob("*e*"), vec![("apple", 2), ("better", 3)]),
+            t5: (glob("[ab]*"), vec![("a", 0), ("app", 1), ("apple", 2), ("application", 4), ("better", 3)]),
+            t6: (glob("?"), vec![("a", 0)]),
+            t7: (glob(""), Vec::<(&str, u8)>::new()),
+            t8: (glob("*"), vec![("a", 0), ("app", 1), ("apple", 2), ("application", 4), ("better", 3), ("アップル🍎", 5)]),
+            t9: (vec![Exact(b'a'), AnySeq, OneOf(vec![b'n', b'e'])], vec![("apple", 2), ("application", 4)]),
+            t10: (vec![Any, Any, Any, Exact(b'l'), AnySeq], vec![("apple", 2), ("application", 4)]),
+            t11: (glob("b?t*r"), vec![("better", 3)]),
+            t12: (glob("c*"), Vec::<(&str, u8)>::new()),
+        }
+    }
+
+    mod search_automaton_tests {
+        use crate::automaton::{Levenshtein, StartsWith, Str};
+
+        #[test]
+        fn str() {
+            let trie = super::build_trie();
+            let results: Vec<(String, &u8)> = trie.search_automaton(Str::new("app")).collect();
+            assert_eq!(results, [("app".to_string(), &1)]);
+            let results: Vec<(String, &u8)> = trie.search_automaton(Str::new("appl")).collect();
+            assert_eq!(results, []);
+        }
+
+        #[test]
+        fn starts_with() {
+            let trie = super::build_trie();
+            let results: Vec<(String, &u8)> = trie
+                .search_automaton(StartsWith(Str::new("appl")))
+                .collect();
+            assert_eq!(
+                results,
+                [("apple".to_string(), &2), ("application".to_string(), &4)]
+            );
+            let results: Vec<String> = trie
+                .search_automaton(StartsWith(Str::new("")))
+                .map(|(k, _)| k)
+                .collect();
+            assert_eq!(results.len(), 6);
+        }
+
+        #[test]
+        fn levenshtein_matches_fuzzy_search() {
+            let trie = super::build_trie();
+            for query in ["apple", "appel", "bitter", "", "zzz"] {
+                for max_edits in 0..3 {
+                    let expected: Vec<(String, &u8)> = trie
+                        .fuzzy_search(query, max_edits)
+                        .map(|(k, v, _)| (k, v))
+                        .collect();
+                    let results: Vec<(String, &u8)> = trie
+                        .search_automaton(Levenshtein::new(query, max_edits))
+                        .collect();
+                    assert_eq!(results, expected);
+                }
+            }
+        }
+    }
+
+    mod range_tests {
+        use std::ops::Bound;
+
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (range, expected_results) = $value;
+                    let trie = super::build_trie();
+                    let results: Vec<(String, &u8)> = trie.range(range).collect();
+                    let expected_results: Vec<(String, &u8)> = expected_results.iter().map(|s| (s.0.to_string(), &s.1)).collect();
+                    assert_eq!(results, expected_results);
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: ("app".."b", vec![("app", 1), ("apple", 2), ("application", 4)]),
+            t2: ("app"..="apple", vec![("app", 1), ("apple", 2)]),
+            t3: ("apq".., vec![("better", 3), ("アップル🍎", 5)]),
+            t4: (.."app", vec![("a", 0)]),
+            t5: ("appl".."apple", Vec::<(&str, u8)>::new()),
+            t6: ("appl"..="apple", vec![("apple", 2)]),
+            t7: ((Bound::<String>::Unbounded, Bound::Unbounded), vec![("a", 0), ("app", 1), ("apple", 2), ("application", 4), ("better", 3), ("アップル🍎", 5)]),
+            t8: ((Bound::Excluded("app".to_string()), Bound::Unbounded), vec![("apple", 2), ("application", 4), ("better", 3), ("アップル🍎", 5)]),
+            t9: ((Bound::Excluded("apple".to_string()), Bound::Excluded("better".to_string())), vec![("application", 4)]),
+            t10: ("b".."a", Vec::<(&str, u8)>::new()),
+            t11: ("".."", Vec::<(&str, u8)>::new()),
+            t12: (""..="a", vec![("a", 0)]),
+            t13: ("bz".., vec![("アップル🍎", 5)]),
+        }
+    }
+
+    mod ordered_tests {
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (query, floor, ceiling, prev_before, next_after) = $value;
+                    let trie = super::build_trie();
+                    let owned = |e: Option<(&str, u8)>| e.map(|e| (e.0.to_string(), e.1));
+                    let result: Option<(String, &u8)> = trie.floor(query);
+                    assert_eq!(result.map(|e| (e.0, *e.1)), owned(floor));
+                    let result: Option<(String, &u8)> = trie.ceiling(query);
+                    assert_eq!(result.map(|e| (e.0, *e.1)), owned(ceiling));
+                    let result: Option<(String, &u8)> = trie.prev_before(query);
+                    assert_eq!(result.map(|e| (e.0, *e.1)), owned(prev_before));
+                    let result: Option<(String, &u8)> = trie.next_after(query);
+                    assert_eq!(result.map(|e| (e.0, *e.1)), owned(next_after));
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: ("", None, Some(("a", 0)), None, Some(("a", 0))),
+            t2: ("a", Some(("a", 0)), Some(("a", 0)), None, Some(("app", 1))),
+            t3: ("ap", Some(("a", 0)), Some(("app", 1)), Some(("a", 0)), Some(("app", 1))),
+            t4: ("app", Some(("app", 1)), Some(("app", 1)), Some(("a", 0)), Some(("apple", 2))),
+            t5: ("appla", Some(("app", 1)), Some(("apple", 2)), Some(("app", 1)), Some(("apple", 2))),
+            t6: ("applz", Some(("application", 4)), Some(("better", 3)), Some(("application", 4)), Some(("better", 3))),
+            t7: ("apple", Some(("apple", 2)), Some(("apple", 2)), Some(("app", 1)), Some(("application", 4))),
+            t8: ("b", Some(("application", 4)), Some(("better", 3)), Some(("application", 4)), Some(("better", 3))),
+            t9: ("bz", Some(("better", 3)), Some(("アップル🍎", 5)), Some(("better", 3)), Some(("アップル🍎", 5))),
+            t10: ("アップル🍎", Some(("アップル🍎", 5)), Some(("アップル🍎", 5)), Some(("better", 3)), None),
+            t11: ("アップル🍎🍏", Some(("アップル🍎", 5)), None, Some(("アップル🍎", 5)), None),
+        }
+    }
+
+    #[test]
+    fn first_and_last() {
+        let trie = build_trie();
+        assert_eq!(trie.first(), Some(("a".to_string(), &0)));
+        assert_eq!(trie.last(), Some(("アップル🍎".to_string(), &5)));
+
+        let empty: Trie<u8, u8> = TrieBuilder::new().build();
+        assert_eq!(empty.first::<String, _>(), None);
+        assert_eq!(empty.last::<String, _>(), None);
+        assert_eq!(empty.floor::<String, _>("a"), None);
+        assert_eq!(empty.ceiling::<String, _>("a"), None);
+    }
+
+    mod reverse_tests {
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let query = $value;
+                    let trie = super::build_trie();
+                    let mut forward: Vec<(String, &u8)> = trie.predictive_search(query).collect();
+                    forward.reverse();
+                    let backward: Vec<(String, &u8)> = trie.predictive_search(query).rev().collect();
+                    assert_eq!(backward, forward);
+
+                    let mut forward: Vec<(String, &u8)> = trie.common_prefix_search(query).collect();
+                    forward.reverse();
+                    let backward: Vec<(String, &u8)> = trie.common_prefix_search(query).rev().collect();
+                    assert_eq!(backward, forward);
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: "",
+            t2: "a",
+            t3: "app",
+            t4: "apple",
+            t5: "application",
+            t6: "applications",
+            t7: "b",
+            t8: "c",
+            t9: "アップル🍎",
+        }
+    }
+
+    #[test]
+    fn reverse_interleaved() {
+        let trie = build_trie();
+        let expected: Vec<(String, &u8)> = trie.iter().collect();
+        for fronts in 0..=expected.len() + 1 {
+            let mut iter = trie.iter::<String, _>();
+            let mut front: Vec<_> = iter.by_ref().take(fronts).collect();
+            let back: Vec<_> = iter.by_ref().rev().collect();
+            assert_eq!(iter.next(), None);
+            front.extend(back.into_iter().rev());
+            assert_eq!(front, expected);
+        }
+
+        // Alternate between the two ends.
+        let mut iter = trie.predictive_search::<String, _>("a");
+        let mut results = Vec::new();
+        let mut tail = Vec::new();
+        while let Some(x) = iter.next() {
+            results.push(x);
+            match iter.next_back() {
+                Some(x) => tail.push(x),
+                None => break,
+            }
+        }
+        results.extend(tail.into_iter().rev());
+        let expected: Vec<(String, &u8)> = trie.predictive_search("a").collect();
+        assert_eq!(results, expected);
+
+        let mut iter = trie.common_prefix_search::<String, _>("apple");
+        assert_eq!(iter.next_back(), Some(("apple".to_string(), &2)));
+        assert_eq!(iter.next(), Some(("a".to_string(), &0)));
+        assert_eq!(iter.next(), Some(("app".to_string(), &1)));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn arc_search() {
+        let trie = Arc::new(build_trie());
+        for query in ["", "a", "app", "apple", "b", "z"] {
+            let expected: Vec<(String, u8)> = trie
+                .predictive_search(query)
+                .map(|(k, v)| (k, *v))
+                .collect();
+            let iter = Arc::clone(&trie).into_predictive_search::<String, _>(query);
+            assert_eq!(iter.len(), expected.len());
+            assert_eq!(iter.clone().collect::<Vec<_>>(), expected);
+            let mut backward: Vec<_> = iter.rev().collect();
+            backward.reverse();
+            assert_eq!(backward, expected);
+
+            let expected: Vec<(String, u8)> =
+                trie.postfix_search(query).map(|(k, v)| (k, *v)).collect();
+            let iter = Arc::clone(&trie).into_postfix_search::<String, _>(query);
+            assert_eq!(iter.clone().collect::<Vec<_>>(), expected);
+            let mut backward: Vec<_> = iter.rev().collect();
+            backward.reverse();
+            assert_eq!(backward, expected);
+        }
+
+        let mut iter = Arc::clone(&trie).into_predictive_search::<String, _>("app");
+        assert_eq!(iter.next_back(), Some(("application".to_string(), 4)));
+        assert_eq!(iter.next(), Some(("app".to_string(), 1)));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next_back(), Some(("apple".to_string(), 2)));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn key_ids() {
+        let trie = build_trie();
+        assert_eq!(trie.len(), 6);
+        assert!(!trie.is_empty());
+
+        let keys: Vec<String> = trie.iter().map(|(k, _): (String, &u8)| k).collect();
+        let mut ids: Vec<usize> = keys.iter().map(|k| trie.key_id(k).unwrap()).collect();
+        for (key, &id) in keys.iter().zip(&ids) {
+            assert_eq!(trie.key_by_id::<String, _>(id).as_ref(), Some(key));
+            assert_eq!(Some(&trie.values()[id]), trie.exact_match(key));
+        }
+        ids.sort();
+        assert_eq!(ids, (0..trie.len()).collect::<Vec<_>>());
+
+        assert_eq!(trie.key_id(""), None);
+        assert_eq!(trie.key_id("ap"), None);
+        assert_eq!(trie.key_id("apples"), None);
+        assert_eq!(trie.key_by_id::<String, _>(trie.len()), None);
+
+        let empty: Trie<u8, u8> = TrieBuilder::new().build();
+        assert!(empty.is_empty());
+        assert_eq!(empty.key_id("a"), None);
+        assert_eq!(empty.key_by_id::<String, _>(0), None);
+    }
+
+    #[test]
+    fn merge_with() {
+        let trie = build_trie();
+        let other: Trie<u8, u8> = [("app", 10), ("apricot", 11), ("b", 12)]
+            .into_iter()
+            .map(|(k, v)| (k.bytes(), v))
+            .collect();
+
+        let merged = trie.merge_with(&other, |a, b| a + b);
+        let entries: Vec<(String, &u8)> = merged.iter().collect();
+        assert_eq!(
+            entries,
+            [
+                ("a".into(), &0),
+                ("app".into(), &11),
+                ("apple".into(), &2),
+                ("application".into(), &4),
+                ("apricot".into(), &11),
+                ("b".into(), &12),
+                ("better".into(), &3),
+                ("アップル🍎".into(), &5),
+            ]
+        );
+        assert_eq!(merged.len(), 8);
+        assert_eq!(merged.predictive_search::<String, _>("ap").len(), 4);
+
+        // Keep the newer value, from either side.
+        let newer = other.merge_with(&trie, |_, b| *b);
+        assert_eq!(newer.exact_match("app"), Some(&1));
+        assert_eq!(newer.exact_match("apricot"), Some(&11));
+
+        let empty: Trie<u8, u8> = TrieBuilder::new().build();
+        let merged = empty.merge_with(&trie, |a, _| *a);
+        assert_eq!(format!("{:?}", merged), format!("{:?}", trie));
+        let merged = empty.merge_with(&empty, |a, _| *a);
+        assert!(merged.is_empty());
+    }
+
+    #[test]
+    fn rank_and_nth() {
+        for trie in [
+            build_trie(),
+            trie_builder().build_with(BuildOptions::new().counts()),
+        ] {
+            let entries: Vec<(String, &u8)> = trie.iter().collect();
+            for (i, entry) in entries.iter().enumerate() {
+                assert_eq!(trie.rank(&entry.0), i);
+                assert_eq!(trie.nth(i).as_ref(), Some(entry));
+            }
+            assert_eq!(trie.nth::<String, _>(entries.len()), None);
+
+            // Queries that are not entries.
+            assert_eq!(trie.rank(""), 0);
+            assert_eq!(trie.rank("ap"), 1);
+            assert_eq!(trie.rank("appla"), 2);
+            assert_eq!(trie.rank("apples"), 3);
+            assert_eq!(trie.rank("b"), 4);
+            assert_eq!(trie.rank("bettering"), 5);
+            assert_eq!(trie.rank("z"), 5);
+            assert_eq!(trie.rank("アップル🍎🍏"), 6);
+        }
+
+        let empty: Trie<u8, u8> = TrieBuilder::new().build();
+        assert_eq!(empty.rank("a"), 0);
+        assert_eq!(empty.nth::<String, _>(0), None);
+    }
+
+    #[test]
+    fn build_options() {
+        let options = BuildOptions::new().counts().scores(|v: &u8| *v);
+        let trie = trie_builder().build_with(options);
+        assert_eq!(trie.count_prefix("app"), 3);
+        let results: Vec<(String, &u8)> = trie.top_k_predictive("app", 1).unwrap();
+        assert_eq!(results, [("application".to_string(), &4)]);
+
+        // Equal scores go to the smaller node number.
+        let trie = trie_builder().build_with(BuildOptions::new().scores(|_: &u8| 0));
+        let results: Vec<(String, &u8)> = trie.top_k_predictive("", 3).unwrap();
+        assert_eq!(
+            results,
+            [
+                ("a".to_string(), &0),
+                ("app".to_string(), &1),
+                ("apple".to_string(), &2)
+            ]
+        );
+    }
+
+    #[test]
+    fn top_k_predictive_without_scores() {
+        let trie = build_trie();
+        let results: Option<Vec<(String, &u8)>> = trie.top_k_predictive("a", 1);
+        assert_eq!(results, None);
+        let trie = trie_builder().build_with(BuildOptions::new().counts());
+        let results: Option<Vec<(String, &u8)>> = trie.top_k_predictive("zzz", 0);
+        assert_eq!(results, None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let trie = trie_builder().build_with(BuildOptions::new().counts());
+        let json = serde_json::to_value(&trie).unwrap();
+        assert!(json.get("fingerprint").is_none());
+
+        let loaded: Trie<u8, u8> = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", trie));
+
+        // Tries serialized before the side structures were added, or with a
+        // fingerprint, still load, and work out the fingerprint again.
+        let mut old = json;
+        let fields = old.as_object_mut().unwrap();
+        fields.remove("subtree_counts");
+        fields.remove("score_ranks");
+        fields.remove("insertion_ranks");
+        fields.insert("fingerprint".into(), 0.into());
+        let loaded: Trie<u8, u8> = serde_json::from_value(old).unwrap();
+        assert!(loaded.subtree_counts.is_none());
+        assert!(loaded.score_ranks.is_none());
+        assert!(loaded.insertion_ranks.is_none());
+        assert!(loaded.fingerprint.get().is_none());
+        assert_eq!(loaded.fingerprint(), trie.fingerprint());
+        let entries: Vec<(String, &u8)> = loaded.iter().collect();
+        assert_eq!(entries, trie.iter().collect::<Vec<(String, &u8)>>());
+        assert_eq!(loaded.count_prefix("app"), 3);
+    }
+
+    #[test]
+    fn dense_node() {
+        // Every byte under the root, and a few under a dense child.
+        let keys: Vec<Vec<u8>> = (0..=255u8)
+            .map(|b| vec![b])
+            .chain((0..=255u8).step_by(3).map(|b| vec![b'x', b]))
+            .collect();
+        let trie: Trie<u8, usize> = keys.iter().cloned().zip(0..).collect();
+        let bytes: Trie<i16, usize> = keys
+            .iter()
+            .map(|k| k.iter().map(|&b| i16::from(b)).collect::<Vec<_>>())
+            .zip(0..)
+            .collect();
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(trie.exact_match(key), Some(&i));
+            let wide: Vec<i16> = key.iter().map(|&b| i16::from(b)).collect();
+            assert_eq!(bytes.exact_match(wide), Some(&i));
+        }
+        assert_eq!(trie.exact_match([b'x', 1]), None);
+        assert_eq!(
+            trie.ceiling::<Vec<u8>, _>([b'x', 1]),
+            Some((vec![b'x', 3], &257))
+        );
+        assert_eq!(
+            trie.floor::<Vec<u8>, _>([b'x', 1]),
+            Some((vec![b'x', 0], &256))
+        );
+    }
+
+    mod count_prefix_tests {
+        use crate::map::BuildOptions;
+
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (query, expected) = $value;
+                    for trie in [super::build_trie(), super::trie_builder().build_with(BuildOptions::new().counts())] {
+                        assert_eq!(trie.count_prefix(query), expected);
+
+                        let mut results = trie.predictive_search::<String, _>(query);
+                        assert_eq!(results.len(), expected);
+                        let mut left = expected;
+                        while results.next().is_some() {
+                            left -= 1;
+                            assert_eq!(results.size_hint(), (left, Some(left)));
+                            if results.next_back().is_some() {
+                                left -= 1;
+                                assert_eq!(results.len(), left);
+                            }
+                        }
+                        assert_eq!(left, 0);
+                    }
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: ("", 6),
+            t2: ("a", 4),
+            t3: ("ap", 3),
+            t4: ("appl", 2),
+            t5: ("application", 1),
+            t6: ("applications", 0),
+            t7: ("b", 1),
+            t8: ("c", 0),
+            t9: ("アップル", 1),
+        }
+    }
+
+    mod top_k_predictive_tests {
+        use crate::map::BuildOptions;
+
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (query, k, expected_results) = $value;
+                    let expected_results: Vec<(String, &u8)> = expected_results.iter().map(|s| (s.0.to_string(), &s.1)).collect();
+                    let trie = super::build_trie();
+                    let results: Vec<(String, &u8)> = trie.top_k_predictive_by(query, k, |v| *v);
+                    assert_eq!(results, expected_results);
+
+                    let options = BuildOptions::new().scores(|v: &u8| *v);
+                    let trie = super::trie_builder().build_with(options);
+                    let results: Vec<(String, &u8)> = trie.top_k_predictive(query, k).unwrap();
+                    assert_eq!(results, expected_results);
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: ("", 3, vec![("アップル🍎", 5), ("application", 4), ("better", 3)]),
+            t2: ("a", 2, vec![("application", 4), ("apple", 2)]),
+            t3: ("app", 10, vec![("application", 4), ("apple", 2), ("app", 1)]),
+            t4: ("apple", 1, vec![("apple", 2)]),
+            t5: ("a", 0, Vec::<(&str, u8)>::new()),
+            t6: ("c", 3, Vec::<(&str, u8)>::new()),
+            t7: ("", 6, vec![("アップル🍎", 5), ("application", 4), ("better", 3), ("apple", 2), ("app", 1), ("a", 0)]),
+        }
+    }
+
+    mod longest_match_tests {
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (query, expected) = $value;
+                    let trie = super::build_trie();
+                    let result = trie.longest_match(query).map(|(len, v)| (len, *v));
+                    assert_eq!(result, expected);
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: ("", None),
+            t2: ("a", Some((1, 0))),
+            t3: ("ap", Some((1, 0))),
+            t4: ("appl", Some((3, 1))),
+            t5: ("applesauce", Some((5, 2))),
+            t6: ("applications", Some((11, 4))),
+            t7: ("bet", None),
+            t8: ("betters", Some((6, 3))),
+            t9: ("z", None),
+        }
+    }
+
     mod postfix_search_tests {
         macro_rules! parameterized_tests {
             ($($name:ident: $value:expr,)*) => {
@@ -1,7 +1,10 @@
 use crate::internal_data_structure::naive_trie::NaiveTrie;
-use crate::map::{Trie, TrieBuilder};
-use fid::bit_vec;
-use louds::Louds;
+use crate::internal_data_structure::packed_ints::PackedInts;
+use crate::map::{BuildOptions, Ranks, Trie, TrieBuilder};
+use fid::{bit_vec, BitVector, FID};
+use louds::{Louds, LoudsNodeNum};
+use std::cmp::Ordering;
+use std::sync::OnceLock;
 
 impl<Label: Ord, Value> Default for TrieBuilder<Label, Value> {
     fn default() -> Self {
@@ -13,7 +16,10 @@ impl<Label: Ord, Value> TrieBuilder<Label, Value> {
     /// Return a [TrieBuilder].
     pub fn new() -> Self {
         let naive_trie = NaiveTrie::make_root();
-        Self { naive_trie }
+        Self {
+            naive_trie,
+            entered: 0,
+        }
     }
 
     /// Gets the entry for the given sequence for in-place manipulation.
@@ -21,7 +27,8 @@ impl<Label: Ord, Value> TrieBuilder<Label, Value> {
         &mut self,
         key: Key,
     ) -> &mut Option<Value> {
-        self.naive_trie.entry(key.into_iter())
+        self.entered += 1;
+        self.naive_trie.entry(key.into_iter(), self.entered - 1)
     }
 
     /// Insert a value for the given sequence.
@@ -35,10 +42,16 @@ impl<Label: Ord, Value> TrieBuilder<Label, Value> {
 
     /// Build a [Trie].
     pub fn build(self) -> Trie<Label, Value> {
+        self.build_with(BuildOptions::new())
+    }
+
+    /// Build a [Trie] that also stores what `options` asks for.
+    pub fn build_with(self, options: BuildOptions<'_, Value>) -> Trie<Label, Value> {
         let mut louds_bits = bit_vec![true, false];
         let mut labels: Vec<Label> = vec![];
         let mut terminals = bit_vec![false, false];
         let mut values: Vec<Value> = vec![];
+        let mut orders: Vec<usize> = vec![];
 
         for node in self.naive_trie.into_iter() {
             match node {
@@ -49,6 +62,9 @@ impl<Label: Ord, Value> TrieBuilder<Label, Value> {
 
                     let is_terminal = if let Some(value) = n.value {
                         values.push(value);
+                        if options.insertion_order {
+                            orders.push(n.order.unwrap_or(usize::MAX));
+                        }
                         true
                     } else {
                         false
@@ -61,23 +77,162 @@ impl<Label: Ord, Value> TrieBuilder<Label, Value> {
             }
         }
 
-        louds_bits.shrink_to_fit();
-        labels.shrink_to_fit();
-        terminals.shrink_to_fit();
-        values.shrink_to_fit();
-
-        let louds = if cfg!(debug_assertions) {
-            Louds::new(louds_bits).unwrap()
-        } else {
-            // SAFETY: NaiveTrie should produce valid bits
-            unsafe { Louds::new_unchecked(louds_bits) }
-        };
-
-        Trie {
-            louds,
-            labels,
-            terminals,
-            values,
+        let mut trie = from_parts(louds_bits, labels, terminals, values);
+        let num_nodes = trie.labels.len() + 1;
+        if options.counts {
+            let counts = subtree_counts(&trie.louds, &trie.terminals, num_nodes);
+            trie.subtree_counts = Some(counts);
+        }
+        if let Some(compare) = options.scores {
+            let ranks = ranks(
+                &trie.louds,
+                &trie.terminals,
+                &trie.values,
+                num_nodes,
+                compare,
+            );
+            trie.score_ranks = Some(ranks);
+        }
+        if options.insertion_order {
+            // The earliest entered key ranks highest.
+            let ranks = ranks(&trie.louds, &trie.terminals, &orders, num_nodes, |a, b| {
+                b.cmp(a)
+            });
+            trie.insertion_ranks = Some(ranks);
+        }
+        trie
+    }
+}
+
+impl<'a, Value> Default for BuildOptions<'a, Value> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, Value> BuildOptions<'a, Value> {
+    /// Return [BuildOptions] that store nothing besides the entries.
+    pub fn new() -> Self {
+        Self {
+            counts: false,
+            scores: None,
+            insertion_order: false,
         }
     }
+
+    /// Store the number of entries under each node.
+    ///
+    /// Each count is packed into as many bits as it takes to write the number
+    /// of entries, so _log n_ bits per node for _n_ entries. They make
+    /// [Trie::count_prefix], [Trie::rank], [Trie::nth] and the length of
+    /// [Trie::predictive_search] take time proportional to the query rather
+    /// than to the height of the trie.
+    pub fn counts(mut self) -> Self {
+        self.counts = true;
+        self
+    }
+
+    /// Store where each entry ranks by `score`, and the best rank under each
+    /// node, for [Trie::top_k_predictive].
+    ///
+    /// This takes _log n_ bits per entry and per node for _n_ entries, and
+    /// lets the search skip every subtree that cannot beat the current `k`th
+    /// result. `score` is only called while building.
+    pub fn scores<S: Ord>(mut self, score: impl Fn(&Value) -> S + 'a) -> Self {
+        self.scores = Some(Box::new(move |a, b| score(a).cmp(&score(b))));
+        self
+    }
+
+    /// Store where each entry ranks in the order its key was first entered
+    /// into the [TrieBuilder], and the earliest rank under each node, for
+    /// [MatchKind::LeftmostFirst][crate::aho_corasick::MatchKind::LeftmostFirst].
+    ///
+    /// This takes _log n_ bits per entry and per node for _n_ entries.
+    pub fn insertion_order(mut self) -> Self {
+        self.insertion_order = true;
+        self
+    }
+}
+
+/// Assemble a [Trie] from its LOUDS bits, the label and terminal flag of each
+/// node, and the values of the terminals, all in breadth-first order.
+pub(crate) fn from_parts<Label, Value>(
+    mut louds_bits: BitVector,
+    mut labels: Vec<Label>,
+    mut terminals: BitVector,
+    mut values: Vec<Value>,
+) -> Trie<Label, Value> {
+    louds_bits.shrink_to_fit();
+    labels.shrink_to_fit();
+    terminals.shrink_to_fit();
+    values.shrink_to_fit();
+
+    let louds = if cfg!(debug_assertions) {
+        Louds::new(louds_bits).unwrap()
+    } else {
+        // SAFETY: Callers build the bits breadth first, a 1 for each child
+        // and a 0 after the children of each node.
+        unsafe { Louds::new_unchecked(louds_bits) }
+    };
+
+    Trie {
+        louds,
+        labels,
+        terminals,
+        values,
+        subtree_counts: None,
+        score_ranks: None,
+        insertion_ranks: None,
+        fingerprint: OnceLock::new(),
+    }
+}
+
+/// Count the terminals in each node's subtree. Nodes are numbered breadth
+/// first, so every child is counted before its parent.
+fn subtree_counts(louds: &Louds<BitVector>, terminals: &BitVector, num_nodes: usize) -> PackedInts {
+    let mut counts: Vec<usize> = (0..=num_nodes as u64)
+        .map(|i| usize::from(terminals[i]))
+        .collect();
+    for node in (2..=num_nodes as u64).rev() {
+        let parent = louds.child_to_parent(louds.node_num_to_index(LoudsNodeNum(node)));
+        counts[parent.0 as usize] += counts[node as usize];
+    }
+    PackedInts::new(&counts)
+}
+
+/// Rank the values from the highest to the lowest by `compare`, ties going
+/// to the smaller node number, and find the best rank in each node's subtree.
+fn ranks<Value>(
+    louds: &Louds<BitVector>,
+    terminals: &BitVector,
+    values: &[Value],
+    num_nodes: usize,
+    compare: impl Fn(&Value, &Value) -> Ordering,
+) -> Ranks {
+    // Values are in node order, and the sort is stable.
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| compare(&values[b], &values[a]));
+    let mut ranks = vec![0; values.len()];
+    for (rank, &i) in order.iter().enumerate() {
+        ranks[i] = rank;
+    }
+
+    let mut best: Vec<usize> = (0..=num_nodes as u64)
+        .map(|i| {
+            if terminals[i] {
+                ranks[terminals.rank1(i) as usize]
+            } else {
+                values.len()
+            }
+        })
+        .collect();
+    for node in (2..=num_nodes as u64).rev() {
+        let parent = louds.child_to_parent(louds.node_num_to_index(LoudsNodeNum(node)));
+        let parent = parent.0 as usize;
+        best[parent] = best[parent].min(best[node as usize]);
+    }
+    Ranks {
+        values: PackedInts::new(&ranks),
+        subtrees: PackedInts::new(&best),
+    }
 }
@@ -0,0 +1,226 @@
+//! Handles to the nodes of a trie
+//!
+//! A [NodeRef] points at one node of a [Trie] and can move to its parent and
+//! children, which is enough to write search algorithms that the trie does
+//! not come with. Each node stands for the key that leads to it from the
+//! root, whether that is an entry or only a prefix of some.
+//!
+//! ```rust
+//! use trie::map::Trie;
+//! use trie::node_ref::NodeRef;
+//!
+//! let trie = Trie::from_iter(
+//!     [("a", 0), ("app", 1), ("apple", 2), ("better", 3)].map(|(k, v)| (k.bytes(), v)),
+//! );
+//!
+//! // Find the entries that no other entry starts with.
+//! fn leaves<'a>(node: NodeRef<'a, u8, i32>, found: &mut Vec<String>) {
+//!     if !node.has_children() {
+//!         found.push(node.key());
+//!     }
+//!     for child in node.children() {
+//!         leaves(child, found);
+//!     }
+//! }
+//! let mut found = Vec::new();
+//! leaves(trie.root(), &mut found);
+//! assert_eq!(found, ["apple", "better"]);
+//!
+//! let node = trie.root().child(&b'a').unwrap().child(&b'p').unwrap();
+//! assert_eq!(node.label(), Some(&b'p'));
+//! assert_eq!(node.depth(), 2);
+//! assert_eq!(node.value(), None);
+//! assert_eq!(node.parent().unwrap().value(), Some(&0));
+//! ```
+
+use crate::map::Trie;
+use crate::try_collect::TryFromIterator;
+use fid::BitVector;
+use louds::{ChildNodeIter, LoudsNodeNum};
+use std::fmt;
+
+/// A node of a [Trie].
+pub struct NodeRef<'a, Label, Value> {
+    trie: &'a Trie<Label, Value>,
+    node: LoudsNodeNum,
+}
+
+impl<'a, Label, Value> Clone for NodeRef<'a, Label, Value> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, Label, Value> Copy for NodeRef<'a, Label, Value> {}
+
+impl<'a, Label, Value> fmt::Debug for NodeRef<'a, Label, Value> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeRef").field("node", &self.node).finish()
+    }
+}
+
+impl<'a, Label: Ord, Value> NodeRef<'a, Label, Value> {
+    pub(crate) fn new(trie: &'a Trie<Label, Value>, node: LoudsNodeNum) -> Self {
+        Self { trie, node }
+    }
+
+    /// Return true if this is the root, which stands for the empty key.
+    pub fn is_root(&self) -> bool {
+        self.node.0 == 1
+    }
+
+    /// Return the last label of the key, or `None` for the root.
+    pub fn label(&self) -> Option<&'a Label> {
+        (!self.is_root()).then(|| self.trie.label(self.node))
+    }
+
+    /// Return true if the key is an entry.
+    pub fn is_terminal(&self) -> bool {
+        self.trie.is_terminal(self.node)
+    }
+
+    /// Return the value of the key if it is an entry.
+    pub fn value(&self) -> Option<&'a Value> {
+        self.trie.value(self.node)
+    }
+
+    /// Return the key that leads to this node.
+    pub fn key<C, M>(&self) -> C
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.trie.key(self.node)
+    }
+
+    /// Return the number of labels in the key, which is 0 for the root.
+    pub fn depth(&self) -> usize {
+        self.trie.child_to_ancestors(self.node).count()
+    }
+
+    /// Return the parent, or `None` for the root.
+    pub fn parent(&self) -> Option<Self> {
+        self.trie
+            .parent_node(self.node)
+            .map(|node| Self::new(self.trie, node))
+    }
+
+    /// Return the child with the label `label`.
+    pub fn child(&self, label: &Label) -> Option<Self> {
+        self.trie
+            .child(self.node, label)
+            .map(|node| Self::new(self.trie, node))
+    }
+
+    /// Return true if some key goes on past this one.
+    pub fn has_children(&self) -> bool {
+        self.trie.has_children_node_nums(self.node)
+    }
+
+    /// Iterate over the children in label order.
+    pub fn children(&self) -> Children<'a, Label, Value> {
+        Children {
+            trie: self.trie,
+            iter: self.trie.children_node_nums(self.node),
+        }
+    }
+}
+
+/// Iterates over the children of a [NodeRef] in label order.
+pub struct Children<'a, Label, Value> {
+    trie: &'a Trie<Label, Value>,
+    iter: ChildNodeIter<'a, BitVector>,
+}
+
+impl<'a, Label: Ord, Value> Iterator for Children<'a, Label, Value> {
+    type Item = NodeRef<'a, Label, Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|node| NodeRef::new(self.trie, node))
+    }
+}
+
+impl<'a, Label: Ord, Value> DoubleEndedIterator for Children<'a, Label, Value> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|node| NodeRef::new(self.trie, node))
+    }
+}
+
+#[cfg(test)]
+mod search_tests {
+    use crate::map::{Trie, TrieBuilder};
+
+    fn build_trie() -> Trie<u8, u8> {
+        let mut builder = TrieBuilder::new();
+        builder.insert("a".bytes(), 0);
+        builder.insert("app".bytes(), 1);
+        builder.insert("apple".bytes(), 2);
+        builder.insert("better".bytes(), 3);
+        builder.insert("application".bytes(), 4);
+        builder.insert("アップル🍎".bytes(), 5);
+        builder.build()
+    }
+
+    #[test]
+    fn walk() {
+        let trie = build_trie();
+        let root = trie.root();
+        assert!(root.is_root());
+        assert_eq!(root.label(), None);
+        assert_eq!(root.value(), None);
+        assert_eq!(root.depth(), 0);
+        assert_eq!(root.key::<String, _>(), "");
+        assert!(root.parent().is_none());
+        assert!(root.child(&b'z').is_none());
+
+        let labels: Vec<u8> = root.children().map(|c| *c.label().unwrap()).collect();
+        assert_eq!(labels, [b'a', b'b', 0xe3]);
+        let labels: Vec<u8> = root.children().rev().map(|c| *c.label().unwrap()).collect();
+        assert_eq!(labels, [0xe3, b'b', b'a']);
+
+        let mut node = root;
+        for (i, label) in "appl".bytes().enumerate() {
+            let child = node.child(&label).unwrap();
+            assert_eq!(child.label(), Some(&label));
+            assert_eq!(child.depth(), i + 1);
+            assert_eq!(
+                child.parent().unwrap().key::<String, _>(),
+                node.key::<String, _>()
+            );
+            node = child;
+        }
+        assert_eq!(node.key::<String, _>(), "appl");
+        assert!(!node.is_terminal());
+        let keys: Vec<String> = node.children().map(|c| c.key()).collect();
+        assert_eq!(keys, ["apple", "appli"]);
+    }
+
+    #[test]
+    fn entries() {
+        let trie = build_trie();
+        // Visit every node depth first and check it against the entries.
+        let mut found = Vec::new();
+        let mut stack = vec![trie.root()];
+        while let Some(node) = stack.pop() {
+            assert_eq!(node.is_terminal(), node.value().is_some());
+            assert_eq!(node.has_children(), node.children().next().is_some());
+            if let Some(value) = node.value() {
+                found.push((node.key::<Vec<u8>, _>(), *value));
+            }
+            stack.extend(node.children().rev());
+        }
+        let entries: Vec<(Vec<u8>, u8)> = trie.iter().map(|(k, v)| (k, *v)).collect();
+        assert_eq!(found, entries);
+
+        let search = {
+            let mut search = trie.inc_search();
+            search.query_until("bet").unwrap();
+            search
+        };
+        let node = search.node();
+        assert_eq!(node.key::<String, _>(), "bet");
+        assert_eq!(node.parent().unwrap().label(), Some(&b'e'));
+    }
+}
@@ -0,0 +1,101 @@
+//! Patterns for wildcard searches
+//!
+//! A pattern is a sequence of [PatternElem]s, each of which matches labels at
+//! one position of a key. It is used by
+//! [`pattern_search()`][crate::map::Trie::pattern_search].
+//!
+//! ```rust
+//! use trie::Trie;
+//! use trie::pattern::PatternElem::{Any, Exact};
+//!
+//! let trie = Trie::from_iter(["cat", "cot", "cut", "coat"].into_iter().map(|s| s.bytes()));
+//! let results: Vec<String> = trie.pattern_search([Exact(b'c'), Any, Exact(b't')]).collect();
+//! assert_eq!(results, ["cat", "cot", "cut"]);
+//! ```
+//!
+//! For byte tries, [glob] builds a pattern from a glob-style string.
+
+/// One position of a pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternElem<Label> {
+    /// Matches exactly this label.
+    Exact(Label),
+    /// Matches any one of these labels.
+    OneOf(Vec<Label>),
+    /// Matches any single label, like `?` in a glob.
+    Any,
+    /// Matches any sequence of labels, including an empty one, like `*` in a
+    /// glob.
+    AnySeq,
+}
+
+impl<Label: Ord> PatternElem<Label> {
+    /// Is `label` accepted by this element when it consumes one label?
+    pub(crate) fn accepts(&self, label: &Label) -> bool {
+        match self {
+            PatternElem::Exact(l) => l == label,
+            PatternElem::OneOf(set) => set.binary_search(label).is_ok(),
+            PatternElem::Any | PatternElem::AnySeq => true,
+        }
+    }
+}
+
+/// Parse a glob-style pattern over bytes.
+///
+/// - `?` matches any single byte.
+/// - `*` matches any sequence of bytes.
+/// - `[abc]` matches one of the listed bytes, and `[a-z]` a range of them.
+/// - `\` matches the next byte literally.
+///
+/// An unterminated `[` is matched literally.
+///
+/// ```rust
+/// use trie::pattern::{glob, PatternElem::*};
+///
+/// assert_eq!(glob("c?t*"), [Exact(b'c'), Any, Exact(b't'), AnySeq]);
+/// assert_eq!(glob("[a-c]\\?"), [OneOf(vec![b'a', b'b', b'c']), Exact(b'?')]);
+/// ```
+pub fn glob(pattern: &str) -> Vec<PatternElem<u8>> {
+    let bytes = pattern.as_bytes();
+    let mut elems = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let elem = match bytes[i] {
+            b'?' => PatternElem::Any,
+            b'*' => PatternElem::AnySeq,
+            b'\\' if i + 1 < bytes.len() => {
+                i += 1;
+                PatternElem::Exact(bytes[i])
+            }
+            b'[' => match bytes[i + 1..].iter().position(|&b| b == b']') {
+                Some(len) => {
+                    let class = &bytes[i + 1..i + 1 + len];
+                    i += len + 1;
+                    PatternElem::OneOf(parse_class(class))
+                }
+                None => PatternElem::Exact(b'['),
+            },
+            b => PatternElem::Exact(b),
+        };
+        elems.push(elem);
+        i += 1;
+    }
+    elems
+}
+
+fn parse_class(class: &[u8]) -> Vec<u8> {
+    let mut set = Vec::new();
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == b'-' {
+            set.extend(class[i]..=class[i + 2]);
+            i += 3;
+        } else {
+            set.push(class[i]);
+            i += 1;
+        }
+    }
+    set.sort_unstable();
+    set.dedup();
+    set
+}
@@ -0,0 +1,350 @@
+//! Dictionary segmentation of a text
+//!
+//! A [Segmenter] splits a text into a sequence of keys of a trie, such as the
+//! words of a sentence written without spaces. It builds the lattice of every
+//! key starting at every position of the text, the way
+//! [`common_prefix_search()`][crate::map::Trie::common_prefix_search] finds
+//! them, and picks the paths through it with the lowest total cost.
+//!
+//! ```rust
+//! use trie::map::Trie;
+//!
+//! let trie = Trie::from_iter(
+//!     [("東京", 8), ("東", 5), ("京都", 10), ("京", 5), ("都", 5)]
+//!         .map(|(k, v)| (k.chars(), v)),
+//! );
+//! let text: Vec<char> = "東京都".chars().collect();
+//! let best = trie.segment(&text, |cost| *cost).unwrap();
+//! assert_eq!(best.cost, 13);
+//! let words: Vec<String> = best
+//!     .segments
+//!     .iter()
+//!     .map(|s| text[s.start..s.end].iter().collect())
+//!     .collect();
+//! assert_eq!(words, ["東京", "都"]);
+//! ```
+//!
+//! Texts with labels that no key covers only have a segmentation if the
+//! [Segmenter] is given an [unknown()][Segmenter::unknown] handler.
+
+use crate::map::Trie;
+use louds::LoudsNodeNum;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// One piece of a [Segmentation].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment<'a, Value> {
+    /// The offset of the first label of the segment.
+    pub start: usize,
+    /// The offset past the last label of the segment.
+    pub end: usize,
+    /// The value of the key, or `None` if the unknown handler made the
+    /// segment.
+    pub value: Option<&'a Value>,
+}
+
+/// A way to split a text into [Segment]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segmentation<'a, Value> {
+    /// The segments, in order, covering the whole text.
+    pub segments: Vec<Segment<'a, Value>>,
+    /// The sum of the costs of the segments.
+    pub cost: i64,
+}
+
+type CostFn<'a, Value> = Box<dyn Fn(&Value) -> i64 + 'a>;
+type UnknownFn<'a, Label> = Box<dyn Fn(&[Label], usize) -> Option<(usize, i64)> + 'a>;
+
+/// Splits texts into keys of a [Trie] at the lowest cost.
+pub struct Segmenter<'a, Label, Value> {
+    trie: &'a Trie<Label, Value>,
+    cost: CostFn<'a, Value>,
+    unknown: Option<UnknownFn<'a, Label>>,
+}
+
+/// An edge of the lattice, starting where it is stored.
+struct Edge {
+    end: usize,
+    cost: i64,
+    /// The terminal node of the key, or 0 for an unknown segment.
+    node: u64,
+}
+
+/// A path from the start of the lattice.
+struct Path {
+    end: usize,
+    cost: i64,
+    /// The last edge, as its start and index there.
+    edge: Option<(usize, usize)>,
+    /// The index of the path without the last edge.
+    parent: usize,
+}
+
+impl<'a, Label: Ord, Value> Segmenter<'a, Label, Value> {
+    /// Create a segmenter for the keys of `trie`, where a key costs `cost` of
+    /// its value.
+    pub fn new(trie: &'a Trie<Label, Value>, cost: impl Fn(&Value) -> i64 + 'a) -> Self {
+        Self {
+            trie,
+            cost: Box::new(cost),
+            unknown: None,
+        }
+    }
+
+    /// Set the handler for text that keys may not cover.
+    ///
+    /// It is called with the text and each offset, and returns the length and
+    /// cost of an unknown segment starting there, or `None`. Giving unknown
+    /// segments a high cost makes keys win wherever they fit.
+    ///
+    /// ```rust
+    /// use trie::map::Trie;
+    /// use trie::segment::Segmenter;
+    ///
+    /// let trie = Trie::from_iter([("東京", 8)].map(|(k, v)| (k.chars(), v)));
+    /// let text: Vec<char> = "東京タワー".chars().collect();
+    /// assert_eq!(trie.segment(&text, |cost| *cost), None);
+    ///
+    /// // Group runs of katakana into one unknown segment.
+    /// let is_katakana = |c: &char| ('\u{30A0}'..='\u{30FF}').contains(c);
+    /// let segmenter = Segmenter::new(&trie, |cost| *cost).unknown(move |text: &[char], i| {
+    ///     let len = text[i..].iter().take_while(|c| is_katakana(c)).count();
+    ///     (len > 0).then_some((len, 100))
+    /// });
+    /// let best = segmenter.best(&text).unwrap();
+    /// let spans: Vec<_> = best.segments.iter().map(|s| (s.start, s.end)).collect();
+    /// assert_eq!(spans, [(0, 2), (2, 5)]);
+    /// assert_eq!(best.cost, 108);
+    /// ```
+    pub fn unknown(
+        mut self,
+        handler: impl Fn(&[Label], usize) -> Option<(usize, i64)> + 'a,
+    ) -> Self {
+        self.unknown = Some(Box::new(handler));
+        self
+    }
+
+    /// Return the segmentation of `text` with the lowest cost, or `None` if
+    /// there is none.
+    pub fn best(&self, text: impl AsRef<[Label]>) -> Option<Segmentation<'a, Value>> {
+        self.n_best(text, 1).pop()
+    }
+
+    /// Return up to `n` segmentations of `text`, from the lowest cost up.
+    /// Segmentations with equal costs come in no particular order.
+    pub fn n_best(&self, text: impl AsRef<[Label]>, n: usize) -> Vec<Segmentation<'a, Value>> {
+        let text = text.as_ref();
+        let lattice = self.lattice(text);
+        let rest = costs_to_end(&lattice);
+        let mut results = Vec::new();
+        if n == 0 || rest[0].is_none() {
+            return results;
+        }
+
+        // A* from the start, where the exact cost to the end makes complete
+        // paths come out in order of cost.
+        let mut paths = vec![Path {
+            end: 0,
+            cost: 0,
+            edge: None,
+            parent: 0,
+        }];
+        let mut heap = BinaryHeap::new();
+        heap.push(Reverse((rest[0].unwrap_or(0), 0)));
+        while let Some(Reverse((_, id))) = heap.pop() {
+            let Path { end: pos, cost, .. } = paths[id];
+            if pos == text.len() {
+                results.push(self.unwind(&lattice, &paths, id));
+                if results.len() == n {
+                    break;
+                }
+                continue;
+            }
+            for (k, edge) in lattice[pos].iter().enumerate() {
+                if let Some(rest) = rest[edge.end] {
+                    let cost = cost + edge.cost;
+                    paths.push(Path {
+                        end: edge.end,
+                        cost,
+                        edge: Some((pos, k)),
+                        parent: id,
+                    });
+                    heap.push(Reverse((cost + rest, paths.len() - 1)));
+                }
+            }
+        }
+        results
+    }
+
+    /// Return the edges starting at each offset of `text`.
+    fn lattice(&self, text: &[Label]) -> Vec<Vec<Edge>> {
+        (0..text.len())
+            .map(|start| {
+                let mut edges = Vec::new();
+                let mut node = LoudsNodeNum(1);
+                for (i, chr) in text[start..].iter().enumerate() {
+                    match self.trie.child(node, chr) {
+                        Some(child) => node = child,
+                        None => break,
+                    }
+                    if let Some(value) = self.trie.value(node) {
+                        edges.push(Edge {
+                            end: start + i + 1,
+                            cost: (self.cost)(value),
+                            node: node.0,
+                        });
+                    }
+                }
+                let unknown = self.unknown.as_ref().and_then(|f| f(text, start));
+                if let Some((len, cost)) = unknown.filter(|&(len, _)| len > 0) {
+                    edges.push(Edge {
+                        end: (start + len).min(text.len()),
+                        cost,
+                        node: 0,
+                    });
+                }
+                edges
+            })
+            .collect()
+    }
+
+    /// Build the segmentation of the complete path `id`.
+    fn unwind(
+        &self,
+        lattice: &[Vec<Edge>],
+        paths: &[Path],
+        mut id: usize,
+    ) -> Segmentation<'a, Value> {
+        let cost = paths[id].cost;
+        let mut segments = Vec::new();
+        while let Path {
+            edge: Some((start, k)),
+            parent,
+            ..
+        } = paths[id]
+        {
+            let edge = &lattice[start][k];
+            segments.push(Segment {
+                start,
+                end: edge.end,
+                value: (edge.node != 0).then(|| {
+                    self.trie
+                        .value(LoudsNodeNum(edge.node))
+                        .expect("Not a terminal")
+                }),
+            });
+            id = parent;
+        }
+        segments.reverse();
+        Segmentation { segments, cost }
+    }
+}
+
+/// Return the lowest cost from each offset to the end of the text, or `None`
+/// if the end cannot be reached.
+fn costs_to_end(lattice: &[Vec<Edge>]) -> Vec<Option<i64>> {
+    let mut rest = vec![None; lattice.len() + 1];
+    rest[lattice.len()] = Some(0);
+    for start in (0..lattice.len()).rev() {
+        rest[start] = lattice[start]
+            .iter()
+            .filter_map(|edge| rest[edge.end].map(|rest| edge.cost + rest))
+            .min();
+    }
+    rest
+}
+
+#[cfg(test)]
+mod search_tests {
+    use super::{Segment, Segmenter};
+    use crate::map::Trie;
+
+    fn build_trie() -> Trie<char, i64> {
+        Trie::from_iter(
+            [
+                ("東京", 8),
+                ("東", 5),
+                ("京都", 10),
+                ("京", 5),
+                ("都", 5),
+                ("東京都", 20),
+                ("に", 1),
+                ("行く", 3),
+            ]
+            .map(|(k, v)| (k.chars(), v)),
+        )
+    }
+
+    /// Return the words and cost of each segmentation.
+    fn words(text: &str, n: usize) -> Vec<(Vec<String>, i64)> {
+        let trie = build_trie();
+        let text: Vec<char> = text.chars().collect();
+        let results = Segmenter::new(&trie, |cost| *cost).n_best(&text, n);
+        results
+            .into_iter()
+            .map(|seg| {
+                let words = seg
+                    .segments
+                    .iter()
+                    .map(|s| text[s.start..s.end].iter().collect())
+                    .collect();
+                (words, seg.cost)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn best() {
+        assert_eq!(
+            words("東京都に行く", 1),
+            [(
+                vec!["東京".into(), "都".into(), "に".into(), "行く".into()],
+                17
+            )]
+        );
+        assert_eq!(words("", 1), [(vec![], 0)]);
+        assert_eq!(words("大阪", 1), []);
+        assert_eq!(words("東京", 0), []);
+    }
+
+    #[test]
+    fn n_best() {
+        let results = words("東京都", 10);
+        let costs: Vec<i64> = results.iter().map(|r| r.1).collect();
+        assert_eq!(costs, [13, 15, 15, 20]);
+        let mut words: Vec<String> = results[1..3].iter().map(|r| r.0.join("|")).collect();
+        words.sort();
+        assert_eq!(words, ["東|京|都", "東|京都"]);
+        assert_eq!(results[3].0, ["東京都"]);
+        assert_eq!(results[0].0, ["東京", "都"]);
+    }
+
+    #[test]
+    fn unknown() {
+        let trie = build_trie();
+        let text: Vec<char> = "東京タワーに行く".chars().collect();
+        let segmenter = Segmenter::new(&trie, |cost| *cost);
+        assert_eq!(segmenter.best(&text), None);
+
+        let segmenter = segmenter.unknown(|_, _| Some((1, 100)));
+        let best = segmenter.best(&text).unwrap();
+        assert_eq!(best.cost, 8 + 300 + 1 + 3);
+        assert_eq!(
+            best.segments[1],
+            Segment {
+                start: 2,
+                end: 3,
+                value: None
+            }
+        );
+        assert_eq!(best.segments[0].value, Some(&8));
+
+        // Unknown segments past the end are cut short.
+        let segmenter = Segmenter::new(&trie, |cost| *cost)
+            .unknown(|text: &[char], i| (text[i] == 'タ').then_some((10, 1000)));
+        let best = segmenter.best(&text).unwrap();
+        let spans: Vec<_> = best.segments.iter().map(|s| (s.start, s.end)).collect();
+        assert_eq!(spans, [(0, 2), (2, 8)]);
+    }
+}
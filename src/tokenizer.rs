@@ -0,0 +1,243 @@
+//! Greedy longest-match tokenization
+//!
+//! A [WordPiece] tokenizer splits each word of a text into the longest keys of
+//! a trie it starts with, from left to right, the way BERT's WordPiece does.
+//! Pieces after the first of a word are looked up with a continuation marker
+//! such as `##` in front.
+//!
+//! ```rust
+//! use trie::map::Trie;
+//! use trie::tokenizer::WordPiece;
+//!
+//! let vocab = Trie::from_iter(
+//!     [("[UNK]", 0), ("un", 1), ("##aff", 2), ("##able", 3), ("the", 4)]
+//!         .map(|(k, v)| (k.bytes(), v)),
+//! );
+//! let tokenizer = WordPiece::new(&vocab)
+//!     .continuation("##")
+//!     .unknown(vocab.exact_match("[UNK]").unwrap())
+//!     .separators(|b: &u8| b.is_ascii_whitespace());
+//!
+//! let ids: Vec<u32> = tokenizer
+//!     .tokenize("the unaffable cat")
+//!     .into_iter()
+//!     .map(|token| *token.value.unwrap())
+//!     .collect();
+//! assert_eq!(ids, [4, 1, 2, 3, 0]);
+//! ```
+
+use crate::map::Trie;
+use louds::LoudsNodeNum;
+
+/// One token of a text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a, Value> {
+    /// The offset of the first label of the token.
+    pub start: usize,
+    /// The offset past the last label of the token.
+    pub end: usize,
+    /// The value of the key, or the unknown value if the word could not be
+    /// split into keys, which is `None` unless set with
+    /// [WordPiece::unknown].
+    pub value: Option<&'a Value>,
+}
+
+type SeparatorFn<'a, Label> = Box<dyn Fn(&Label) -> bool + 'a>;
+
+/// Splits the words of a text into the longest keys of a [Trie].
+pub struct WordPiece<'a, Label, Value> {
+    trie: &'a Trie<Label, Value>,
+    /// The node of the continuation marker, or `None` if the marker is not a
+    /// prefix of any key.
+    continuation: Option<LoudsNodeNum>,
+    unknown: Option<&'a Value>,
+    max_input_labels_per_word: usize,
+    separators: Option<SeparatorFn<'a, Label>>,
+}
+
+impl<'a, Label: Ord, Value> WordPiece<'a, Label, Value> {
+    /// Create a tokenizer for the keys of `trie`, without a continuation
+    /// marker, unknown value or separators, and at most 100 labels per word.
+    pub fn new(trie: &'a Trie<Label, Value>) -> Self {
+        Self {
+            trie,
+            continuation: Some(LoudsNodeNum(1)),
+            unknown: None,
+            max_input_labels_per_word: 100,
+            separators: None,
+        }
+    }
+
+    /// Set the marker in front of the keys for the pieces of a word after the
+    /// first one, such as `##`.
+    pub fn continuation(mut self, marker: impl AsRef<[Label]>) -> Self {
+        self.continuation = self.trie.prefix_node(marker);
+        self
+    }
+
+    /// Set the value of the token for a word that cannot be split into keys.
+    pub fn unknown(mut self, value: &'a Value) -> Self {
+        self.unknown = Some(value);
+        self
+    }
+
+    /// Set the number of labels above which a word is unknown without being
+    /// looked up.
+    pub fn max_input_labels_per_word(mut self, max: usize) -> Self {
+        self.max_input_labels_per_word = max;
+        self
+    }
+
+    /// Set the labels that separate words. They are not part of any token.
+    pub fn separators(mut self, is_separator: impl Fn(&Label) -> bool + 'a) -> Self {
+        self.separators = Some(Box::new(is_separator));
+        self
+    }
+
+    /// Split `text` into tokens. A word that cannot be split into keys is one
+    /// unknown token.
+    pub fn tokenize(&self, text: impl AsRef<[Label]>) -> Vec<Token<'a, Value>> {
+        let text = text.as_ref();
+        let is_separator = |label: &Label| self.separators.as_ref().is_some_and(|f| f(label));
+        let mut tokens = Vec::new();
+        let mut start = 0;
+        while start < text.len() {
+            if is_separator(&text[start]) {
+                start += 1;
+                continue;
+            }
+            let end = text[start..]
+                .iter()
+                .position(is_separator)
+                .map_or(text.len(), |len| start + len);
+            self.tokenize_word(&text[..end], start, &mut tokens);
+            start = end;
+        }
+        tokens
+    }
+
+    /// Split the word `text[start..]` into tokens.
+    fn tokenize_word(&self, text: &[Label], start: usize, tokens: &mut Vec<Token<'a, Value>>) {
+        let first = tokens.len();
+        if text.len() - start <= self.max_input_labels_per_word {
+            let mut pos = start;
+            while pos < text.len() {
+                let from = if pos == start {
+                    Some(LoudsNodeNum(1))
+                } else {
+                    self.continuation
+                };
+                let found = from.and_then(|node| self.trie.longest_match_node(node, &text[pos..]));
+                match found {
+                    Some((len, node)) => {
+                        tokens.push(Token {
+                            start: pos,
+                            end: pos + len,
+                            value: self.trie.value(node),
+                        });
+                        pos += len;
+                    }
+                    None => break,
+                }
+            }
+            if pos == text.len() {
+                return;
+            }
+        }
+        tokens.truncate(first);
+        tokens.push(Token {
+            start,
+            end: text.len(),
+            value: self.unknown,
+        });
+    }
+}
+
+#[cfg(test)]
+mod search_tests {
+    use super::WordPiece;
+    use crate::map::Trie;
+
+    fn build_trie() -> Trie<u8, u32> {
+        Trie::from_iter(
+            [
+                ("[UNK]", 0),
+                ("un", 1),
+                ("##aff", 2),
+                ("##able", 3),
+                ("the", 4),
+                ("a", 5),
+                ("##a", 6),
+                ("##b", 7),
+                ("ab", 8),
+            ]
+            .map(|(k, v)| (k.bytes(), v)),
+        )
+    }
+
+    mod tokenize_tests {
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (text, expected): (&str, Vec<(usize, usize, u32)>) = $value;
+                    let trie = super::build_trie();
+                    let tokenizer = super::WordPiece::new(&trie)
+                        .continuation("##")
+                        .unknown(trie.exact_match("[UNK]").unwrap())
+                        .max_input_labels_per_word(10)
+                        .separators(|b: &u8| b.is_ascii_whitespace());
+                    let tokens: Vec<_> = tokenizer
+                        .tokenize(text)
+                        .into_iter()
+                        .map(|t| (t.start, t.end, *t.value.unwrap()))
+                        .collect();
+                    assert_eq!(tokens, expected);
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: ("unaffable", vec![(0, 2, 1), (2, 5, 2), (5, 9, 3)]),
+            t2: ("  the  unaffable ", vec![(2, 5, 4), (7, 9, 1), (9, 12, 2), (12, 16, 3)]),
+            t3: ("abab", vec![(0, 2, 8), (2, 3, 6), (3, 4, 7)]),
+            t4: ("unaffablex", vec![(0, 10, 0)]),
+            t5: ("xyz the", vec![(0, 3, 0), (4, 7, 4)]),
+            t6: ("aaaaaaaaaaa a", vec![(0, 11, 0), (12, 13, 5)]),
+            t7: ("aaaaaaaaaa", vec![(0, 1, 5), (1, 2, 6), (2, 3, 6), (3, 4, 6), (4, 5, 6), (5, 6, 6), (6, 7, 6), (7, 8, 6), (8, 9, 6), (9, 10, 6)]),
+            t8: ("", vec![]),
+            t9: ("   ", vec![]),
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let trie = build_trie();
+
+        // No continuation marker, unknown value or separators.
+        let tokenizer = WordPiece::new(&trie);
+        let tokens: Vec<_> = tokenizer
+            .tokenize("theab x")
+            .into_iter()
+            .map(|t| (t.start, t.end, t.value.copied()))
+            .collect();
+        assert_eq!(tokens, [(0, 7, None)]);
+        let tokens: Vec<_> = tokenizer
+            .tokenize("theab")
+            .into_iter()
+            .map(|t| (t.start, t.end, t.value.copied()))
+            .collect();
+        assert_eq!(tokens, [(0, 3, Some(4)), (3, 5, Some(8))]);
+
+        // A continuation marker that no key starts with.
+        let tokenizer = WordPiece::new(&trie).continuation("@@");
+        let tokens: Vec<_> = tokenizer
+            .tokenize("theab")
+            .into_iter()
+            .map(|t| (t.start, t.end, t.value.copied()))
+            .collect();
+        assert_eq!(tokens, [(0, 5, None)]);
+    }
+}
@@ -40,5 +40,11 @@ pub(crate) static EXPECTED_FMT: &str = "Trie(
         values: [
             (),
         ],
+        subtree_counts: None,
+        score_ranks: None,
+        insertion_ranks: None,
+        fingerprint: OnceLock(
+            <uninit>,
+        ),
     },
 )";
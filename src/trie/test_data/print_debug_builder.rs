@@ -16,11 +16,15 @@ pub(crate) static EXPECTED_FMT: &str = "TrieBuilder(
                                                     value: Some(
                                                         (),
                                                     ),
+                                                    order: Some(
+                                                        1,
+                                                    ),
                                                 },
                                             ),
                                         ],
                                         label: 112,
                                         value: None,
+                                        order: None,
                                     },
                                 ),
                             ],
@@ -28,10 +32,14 @@ pub(crate) static EXPECTED_FMT: &str = "TrieBuilder(
                             value: Some(
                                 (),
                             ),
+                            order: Some(
+                                0,
+                            ),
                         },
                     ),
                 ],
             },
         ),
+        entered: 2,
     },
 )";
@@ -22,6 +22,12 @@ impl<Label: Ord> TrieBuilder<Label> {
     pub fn build(self) -> Trie<Label> {
         Trie(self.0.build())
     }
+
+    /// Build a [Trie] that also stores what `options` asks for. See
+    /// [map::BuildOptions].
+    pub fn build_with(self, options: map::BuildOptions<'_, ()>) -> Trie<Label> {
+        Trie(self.0.build_with(options))
+    }
 }
 
 impl<Label: Ord> Default for TrieBuilder<Label> {
@@ -1,8 +1,16 @@
+use crate::aho_corasick::{AhoCorasick, MatchKind};
+use crate::automaton::Automaton;
 use crate::inc_search::IncSearch;
-use crate::iter::{Keys, KeysExt, PostfixIter, PrefixIter, SearchIter};
+use crate::iter::{
+    AutomatonIter, FuzzyIter, FuzzyKeys, Keys, KeysExt, PatternIter, PostfixIter, PrefixIter,
+    RangeIter, SearchIter,
+};
 use crate::map;
+use crate::node_ref::NodeRef;
+use crate::pattern::PatternElem;
 use crate::try_collect::TryFromIterator;
 use std::iter::FromIterator;
+use std::ops::RangeBounds;
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "mem_dbg", derive(mem_dbg::MemDbg, mem_dbg::MemSize))]
@@ -35,6 +43,100 @@ impl<Label: Ord> Trie<Label> {
         self.0.exact_match(query).is_some()
     }
 
+    /// Return the number of entries.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Return true if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Return the ID of `query` if it is an exact match.
+    ///
+    /// IDs are dense integers in `0..len()`, so they can index arrays kept
+    /// alongside the trie. See [map::Trie::key_id].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::Trie;
+    ///
+    /// let trie = Trie::from_iter(["a", "app", "apple"].into_iter().map(|s| s.bytes()));
+    /// let id = trie.key_id("apple").unwrap();
+    /// assert!(id < trie.len());
+    /// assert_eq!(trie.key_by_id::<String, _>(id), Some("apple".to_string()));
+    /// assert_eq!(trie.key_id("ap"), None);
+    /// ```
+    pub fn key_id(&self, query: impl AsRef<[Label]>) -> Option<usize> {
+        self.0.key_id(query)
+    }
+
+    /// Return the number of entries that start with `query`, without visiting
+    /// them. See [map::Trie::count_prefix].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::Trie;
+    ///
+    /// let trie = Trie::from_iter(["a", "app", "apple", "better"].into_iter().map(|s| s.bytes()));
+    /// assert_eq!(trie.count_prefix("app"), 2);
+    /// assert_eq!(trie.predictive_search::<String, _>("a").len(), 3);
+    /// ```
+    pub fn count_prefix(&self, query: impl AsRef<[Label]>) -> usize {
+        self.0.count_prefix(query)
+    }
+
+    /// Return the number of entries lexicographically less than `query`.
+    ///
+    /// This is only quick if the trie was built with
+    /// [BuildOptions::counts][crate::map::BuildOptions::counts], see
+    /// [map::Trie::rank].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::Trie;
+    ///
+    /// let trie = Trie::from_iter(["a", "app", "apple", "better"].into_iter().map(|s| s.bytes()));
+    /// assert_eq!(trie.rank("apple"), 2);
+    /// assert_eq!(trie.rank("b"), 3);
+    /// ```
+    pub fn rank(&self, query: impl AsRef<[Label]>) -> usize {
+        self.0.rank(query)
+    }
+
+    /// Return the `n`th entry in lexicographical order, counting from zero, or
+    /// `None` if `n >= len()`.
+    ///
+    /// This is only quick if the trie was built with
+    /// [BuildOptions::counts][crate::map::BuildOptions::counts], see
+    /// [map::Trie::nth].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::Trie;
+    ///
+    /// let trie = Trie::from_iter(["a", "app", "apple", "better"].into_iter().map(|s| s.bytes()));
+    /// assert_eq!(trie.nth::<String, _>(3), Some("better".to_string()));
+    /// assert_eq!(trie.nth::<String, _>(4), None);
+    /// ```
+    pub fn nth<C, M>(&self, n: usize) -> Option<C>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.0.nth(n).map(|(key, _)| key)
+    }
+
+    /// Return the key with the ID `id`, or `None` if `id >= len()`.
+    pub fn key_by_id<C, M>(&self, id: usize) -> Option<C>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.0.key_by_id(id)
+    }
+
     /// Return the common prefixes of `query`.
     ///
     /// # Arguments
@@ -77,6 +179,91 @@ impl<Label: Ord> Trie<Label> {
         self.0.predictive_search(query).keys()
     }
 
+    /// Return all entries within `max_edits` edits of `query`, along with their
+    /// Levenshtein distance to `query`.
+    pub fn fuzzy_search<C, M>(
+        &self,
+        query: impl AsRef<[Label]>,
+        max_edits: usize,
+    ) -> FuzzyKeys<FuzzyIter<'_, Label, (), C, M>>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        FuzzyKeys::new(self.0.fuzzy_search(query, max_edits))
+    }
+
+    /// Return all entries that have a prefix within `max_edits` edits of
+    /// `query`, along with the smallest such distance.
+    ///
+    /// # Examples
+    /// In the following example we illustrate how a typo in the first
+    /// characters still finds completions.
+    ///
+    /// ```rust
+    /// use trie::Trie;
+    ///
+    /// let trie = Trie::from_iter(["a", "app", "apple", "better", "application"]
+    ///     .into_iter()
+    ///     .map(|s| s.bytes())
+    /// );
+    /// let results: Vec<(String, usize)> = trie.fuzzy_predictive_search("spp", 1).collect();
+    /// assert_eq!(
+    ///     results,
+    ///     [("app".to_string(), 1), ("apple".to_string(), 1), ("application".to_string(), 1)]
+    /// );
+    /// ```
+    pub fn fuzzy_predictive_search<C, M>(
+        &self,
+        query: impl AsRef<[Label]>,
+        max_edits: usize,
+    ) -> FuzzyKeys<FuzzyIter<'_, Label, (), C, M>>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        FuzzyKeys::new(self.0.fuzzy_predictive_search(query, max_edits))
+    }
+
+    /// Return all entries that match `pattern`. See [crate::pattern] for
+    /// details.
+    ///
+    /// # Examples
+    /// In the following example we illustrate how to solve a crossword clue.
+    ///
+    /// ```rust
+    /// use trie::Trie;
+    /// use trie::pattern::glob;
+    ///
+    /// let trie = Trie::from_iter(["cat", "cot", "cut", "coat", "dog"]
+    ///     .into_iter()
+    ///     .map(|s| s.bytes())
+    /// );
+    /// let results: Vec<String> = trie.pattern_search(glob("c?t")).collect();
+    /// assert_eq!(results, vec!["cat", "cot", "cut"]);
+    /// ```
+    pub fn pattern_search<C, M>(
+        &self,
+        pattern: impl AsRef<[PatternElem<Label>]>,
+    ) -> Keys<PatternIter<'_, Label, (), C, M>>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.0.pattern_search(pattern).keys()
+    }
+
+    /// Return all entries that `aut` matches. See [crate::automaton] for
+    /// details.
+    pub fn search_automaton<A, C, M>(&self, aut: A) -> Keys<AutomatonIter<'_, Label, (), A, C, M>>
+    where
+        A: Automaton<Label>,
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.0.search_automaton(aut).keys()
+    }
+
     /// Return the postfixes of all entries that match `query`.
     ///
     /// # Arguments
@@ -128,6 +315,9 @@ impl<Label: Ord> Trie<Label> {
     ///
     /// let results: Vec<String> = trie.iter().collect();
     /// assert_eq!(results, vec!["a", "app", "apple", "application", "better"]);
+    ///
+    /// let results: Vec<String> = trie.iter().rev().collect();
+    /// assert_eq!(results, vec!["better", "application", "apple", "app", "a"]);
     /// ```
     pub fn iter<C, M>(&self) -> Keys<PostfixIter<'_, Label, (), C, M>>
     where
@@ -137,12 +327,114 @@ impl<Label: Ord> Trie<Label> {
         self.postfix_search([])
     }
 
+    /// Return all entries within `range`, in lexicographical order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::Trie;
+    ///
+    /// let trie = Trie::from_iter(["a", "app", "apple", "better", "application"]
+    ///     .into_iter()
+    ///     .map(|s| s.bytes())
+    /// );
+    /// let results: Vec<String> = trie.range("app"..="apple").collect();
+    /// assert_eq!(results, vec!["app", "apple"]);
+    /// ```
+    pub fn range<C, M, K>(&self, range: impl RangeBounds<K>) -> Keys<RangeIter<'_, Label, (), C, M>>
+    where
+        C: TryFromIterator<Label, M>,
+        K: AsRef<[Label]> + ?Sized,
+        Label: Clone,
+    {
+        self.0.range(range).keys()
+    }
+
+    /// Return the smallest entry.
+    pub fn first<C, M>(&self) -> Option<C>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.0.first().map(|(key, _)| key)
+    }
+
+    /// Return the largest entry.
+    pub fn last<C, M>(&self) -> Option<C>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.0.last().map(|(key, _)| key)
+    }
+
+    /// Return the largest entry less than or equal to `query`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::Trie;
+    ///
+    /// let trie = Trie::from_iter(["a", "app", "apple", "better", "application"]
+    ///     .into_iter()
+    ///     .map(|s| s.bytes())
+    /// );
+    /// assert_eq!(trie.floor("apples"), Some("apple".to_string()));
+    /// assert_eq!(trie.ceiling("apples"), Some("application".to_string()));
+    /// assert_eq!(trie.prev_before("app"), Some("a".to_string()));
+    /// assert_eq!(trie.next_after("app"), Some("apple".to_string()));
+    /// ```
+    pub fn floor<C, M>(&self, query: impl AsRef<[Label]>) -> Option<C>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.0.floor(query).map(|(key, _)| key)
+    }
+
+    /// Return the smallest entry greater than or equal to `query`.
+    pub fn ceiling<C, M>(&self, query: impl AsRef<[Label]>) -> Option<C>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.0.ceiling(query).map(|(key, _)| key)
+    }
+
+    /// Return the smallest entry strictly greater than `query`.
+    pub fn next_after<C, M>(&self, query: impl AsRef<[Label]>) -> Option<C>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.0.next_after(query).map(|(key, _)| key)
+    }
+
+    /// Return the largest entry strictly less than `query`.
+    pub fn prev_before<C, M>(&self, query: impl AsRef<[Label]>) -> Option<C>
+    where
+        C: TryFromIterator<Label, M>,
+        Label: Clone,
+    {
+        self.0.prev_before(query).map(|(key, _)| key)
+    }
+
     /// Create an incremental search. Useful for interactive applications. See
     /// [crate::inc_search] for details.
     pub fn inc_search(&self) -> IncSearch<'_, Label, ()> {
         IncSearch::new(&self.0)
     }
 
+    /// Return the root node, to walk the trie by hand. See [crate::node_ref]
+    /// for details.
+    pub fn root(&self) -> NodeRef<'_, Label, ()> {
+        self.0.root()
+    }
+
+    /// Create an [AhoCorasick] matcher to find the keys inside a text. See
+    /// [crate::aho_corasick] for details.
+    pub fn aho_corasick(&self, kind: MatchKind) -> AhoCorasick<'_, Label, ()> {
+        AhoCorasick::new(&self.0, kind)
+    }
+
     /// Return true if `query` is a prefix.
     ///
     /// Note: A prefix may be an exact match or not, and an exact match may be a
@@ -151,7 +443,26 @@ impl<Label: Ord> Trie<Label> {
         self.0.is_prefix(query)
     }
 
+    /// Return the number of labels of the longest key that `query` starts
+    /// with. See [map::Trie::longest_match].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::Trie;
+    ///
+    /// let trie = Trie::from_iter(["a", "app", "apple"].into_iter().map(|s| s.bytes()));
+    /// assert_eq!(trie.longest_match("applause"), Some(3));
+    /// assert_eq!(trie.longest_match("b"), None);
+    /// ```
+    pub fn longest_match(&self, query: impl AsRef<[Label]>) -> Option<usize> {
+        self.0.longest_match(query).map(|(len, _)| len)
+    }
+
     /// Return the longest shared prefix of `query`.
+    ///
+    /// This goes on past `query` as long as there is only one way to go. To
+    /// find the longest key that `query` starts with, see
+    /// [longest_match()][Trie::longest_match].
     pub fn longest_prefix<C, M>(&self, query: impl AsRef<[Label]>) -> Option<C>
     where
         C: TryFromIterator<Label, M>,
@@ -159,6 +470,93 @@ impl<Label: Ord> Trie<Label> {
     {
         self.0.longest_prefix(query)
     }
+
+    /// Return the keys that are in `self`, `other` or both.
+    ///
+    /// This and the other set operations walk both tries together, level by
+    /// level, and lay out the new trie as they go. They take time linear in the
+    /// number of nodes rather than inserting every key into a [TrieBuilder].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::Trie;
+    ///
+    /// let allow = Trie::from_iter(["app", "apple", "bet"].into_iter().map(|s| s.bytes()));
+    /// let block = Trie::from_iter(["apple", "better"].into_iter().map(|s| s.bytes()));
+    ///
+    /// let keys: Vec<String> = allow.union(&block).iter().collect();
+    /// assert_eq!(keys, ["app", "apple", "bet", "better"]);
+    /// let keys: Vec<String> = allow.intersection(&block).iter().collect();
+    /// assert_eq!(keys, ["apple"]);
+    /// let keys: Vec<String> = allow.difference(&block).iter().collect();
+    /// assert_eq!(keys, ["app", "bet"]);
+    /// let keys: Vec<String> = allow.symmetric_difference(&block).iter().collect();
+    /// assert_eq!(keys, ["app", "bet", "better"]);
+    /// ```
+    ///
+    /// [TrieBuilder]: crate::TrieBuilder
+    pub fn union(&self, other: &Self) -> Self
+    where
+        Label: Clone,
+    {
+        Trie(self.0.combine(&other.0, true, true, |_, _| Some(())))
+    }
+
+    /// Return the keys that are in both `self` and `other`. See
+    /// [union()][Trie::union].
+    pub fn intersection(&self, other: &Self) -> Self
+    where
+        Label: Clone,
+    {
+        Trie(self.0.combine(&other.0, false, false, |a, b| {
+            (a.is_some() && b.is_some()).then_some(())
+        }))
+    }
+
+    /// Return the keys that are in `self` but not in `other`. See
+    /// [union()][Trie::union].
+    pub fn difference(&self, other: &Self) -> Self
+    where
+        Label: Clone,
+    {
+        Trie(self.0.combine(&other.0, true, false, |a, b| {
+            (a.is_some() && b.is_none()).then_some(())
+        }))
+    }
+
+    /// Return the keys that are in either `self` or `other` but not both. See
+    /// [union()][Trie::union].
+    pub fn symmetric_difference(&self, other: &Self) -> Self
+    where
+        Label: Clone,
+    {
+        Trie(self.0.combine(&other.0, true, true, |a, b| {
+            (a.is_some() != b.is_some()).then_some(())
+        }))
+    }
+
+    /// Return true if every key of `self` is in `other`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use trie::Trie;
+    ///
+    /// let small = Trie::from_iter(["app", "apple"].into_iter().map(|s| s.bytes()));
+    /// let large = Trie::from_iter(["a", "app", "apple"].into_iter().map(|s| s.bytes()));
+    /// let other = Trie::from_iter(["a", "better"].into_iter().map(|s| s.bytes()));
+    /// assert!(small.is_subset(&large));
+    /// assert!(!large.is_subset(&small));
+    /// assert!(small.is_disjoint(&other));
+    /// assert!(!large.is_disjoint(&other));
+    /// ```
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.0.keys_subset(&other.0)
+    }
+
+    /// Return true if `self` and `other` have no key in common.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.0.keys_disjoint(&other.0)
+    }
 }
 
 impl<Label, Key> FromIterator<Key> for Trie<Label>
@@ -379,6 +777,79 @@ mod search_tests {
         }
     }
 
+    mod fuzzy_predictive_search_tests {
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (query, max_edits, expected_results) = $value;
+                    let trie = super::build_trie();
+                    let results: Vec<(String, usize)> = trie.fuzzy_predictive_search(query, max_edits).collect();
+                    let expected_results: Vec<(String, usize)> = expected_results.iter().map(|s| (s.0.to_string(), s.1)).collect();
+                    assert_eq!(results, expected_results);
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: ("aple", 1, vec![("apple", 1)]),
+            t2: ("bet", 0, vec![("better", 0)]),
+            t3: ("c", 0, Vec::<(&str, usize)>::new()),
+        }
+    }
+
+    mod set_operation_tests {
+        use crate::Trie;
+
+        fn keys(keys: &[&str]) -> Trie<u8> {
+            Trie::from_iter(keys.iter().map(|s| s.bytes()))
+        }
+
+        macro_rules! parameterized_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (a, b, union, intersection, difference, symmetric_difference): (
+                        &[&str],
+                        &[&str],
+                        &[&str],
+                        &[&str],
+                        &[&str],
+                        &[&str],
+                    ) = $value;
+                    let (a, b) = (keys(a), keys(b));
+                    // Compare the whole layout, so that no branch without keys
+                    // is left behind.
+                    let cases = [
+                        (a.union(&b), union),
+                        (a.intersection(&b), intersection),
+                        (a.difference(&b), difference),
+                        (a.symmetric_difference(&b), symmetric_difference),
+                    ];
+                    for (result, expected) in cases {
+                        assert_eq!(format!("{:?}", result), format!("{:?}", keys(expected)));
+                    }
+                    assert_eq!(a.is_subset(&b), difference.is_empty());
+                    assert_eq!(a.is_disjoint(&b), intersection.is_empty());
+                }
+            )*
+            }
+        }
+
+        parameterized_tests! {
+            t1: (&[], &[], &[], &[], &[], &[]),
+            t2: (&["a", "app"], &[], &["a", "app"], &[], &["a", "app"], &["a", "app"]),
+            t3: (&[], &["a", "app"], &["a", "app"], &[], &[], &["a", "app"]),
+            t4: (&["abc"], &["abd"], &["abc", "abd"], &[], &["abc"], &["abc", "abd"]),
+            t5: (&["app"], &["a", "app", "apple"], &["a", "app", "apple"], &["app"], &[], &["a", "apple"]),
+            t6: (&["a", "apple", "b"], &["app", "apple", "c"], &["a", "app", "apple", "b", "c"], &["apple"], &["a", "b"], &["a", "app", "b", "c"]),
+            t7: (&["better", "bet"], &["better", "bet"], &["bet", "better"], &["bet", "better"], &[], &[]),
+        }
+    }
+
     mod common_prefix_search_tests {
         macro_rules! parameterized_tests {
             ($($name:ident: $value:expr,)*) => {